@@ -163,6 +163,14 @@ async fn assume_role_without_mfa() -> Result<CachedCredentials, StsError> {
     })
 }
 
+/// AssumeRole で取得した（キャッシュ済みの）認証情報の有効期限を返す関数
+/// ※ ROLE_ARN が設定されていない、またはキャッシュが無い／期限切れの場合は None
+pub fn session_expiration() -> Option<SystemTime> {
+    dotenv().ok();
+    std::env::var("ROLE_ARN").ok()?;
+    load_cached_credentials().map(|creds| creds.expiration)
+}
+
 /// キャッシュファイル（JSON形式）から認証情報を読み込む関数  
 /// ※ キャッシュが存在し、かつ有効期限が現在よりも先なら Some を返す
 fn load_cached_credentials() -> Option<CachedCredentials> {
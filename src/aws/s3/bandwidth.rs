@@ -0,0 +1,206 @@
+use aws_sdk_s3::primitives::{ByteStream, SdkBody};
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::time::Sleep;
+
+use super::progress::ProgressReporter;
+use super::BoxError;
+
+/// 全転送で共有する帯域制限
+static LIMITER: Mutex<Option<TokenBucket>> = Mutex::new(None);
+
+/// 送信するボディを区切る大きさ（この単位ごとに帯域制限に従って待つ）
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// 1 秒あたりの転送量を rate バイトに抑えるトークンバケット
+/// ※ 最大 1 秒分までのバーストを許可する
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    /// 使えるバイト数（負の場合は先取りした分）
+    available: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    /// bytes を消費し、制限を守るために待つべき時間を返す
+    fn take(&mut self, bytes: u64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.available = (self.available + elapsed * self.rate).min(self.rate);
+        self.available -= bytes as f64;
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / self.rate)
+        }
+    }
+}
+
+/// この crate の S3 転送全体の帯域を制限する（None で制限を解除する）
+/// ※ アップロード・ダウンロードを合わせた、プロセス内のすべての転送の合計に適用する
+/// ※ アップロードは送信するボディを 64 KiB ごとに区切り、区切りごとに待つ
+pub fn set_bandwidth_limit(bytes_per_sec: Option<u64>) {
+    let bucket = bytes_per_sec
+        .filter(|&rate| rate > 0)
+        .map(|rate| TokenBucket {
+            rate: rate as f64,
+            available: rate as f64,
+            last: Instant::now(),
+        });
+    if let Ok(mut limiter) = LIMITER.lock() {
+        *limiter = bucket;
+    }
+}
+
+/// 現在の帯域制限（バイト/秒）
+pub fn bandwidth_limit() -> Option<u64> {
+    LIMITER
+        .lock()
+        .ok()
+        .and_then(|limiter| limiter.as_ref().map(|b| b.rate as u64))
+}
+
+/// 送信するボディを、帯域制限に従って少しずつ送り出すボディに変換する
+/// ※ 制限が設定されていない場合はそのまま返す（途中で設定した制限は、以降に作るボディから適用される）
+/// ※ 再送できるボディ（ファイルやバイト列）は、変換後も SDK のリトライで再送できる
+pub fn throttled_byte_stream(stream: ByteStream) -> ByteStream {
+    upload_byte_stream(stream, None)
+}
+
+/// throttled_byte_stream に加えて、送り出した区切りごとに progress へ転送量を通知する
+/// ※ SDK のリトライで再送した分は、すでに通知した位置を超えるまで数えない
+pub(super) fn upload_byte_stream(
+    stream: ByteStream,
+    progress: Option<&ProgressReporter>,
+) -> ByteStream {
+    if progress.is_none() && bandwidth_limit().is_none() {
+        return stream;
+    }
+    let sent = progress.map(|reporter| SentBytes {
+        reporter: reporter.clone(),
+        reported: Arc::new(AtomicU64::new(0)),
+    });
+    let body = stream.into_inner().map_preserve_contents(move |body| {
+        SdkBody::from_body_1_x(ThrottledBody::new(body, sent.clone()))
+    });
+    ByteStream::new(body)
+}
+
+/// bytes を転送する分だけ、帯域制限に従って待つ
+pub(super) async fn throttle(bytes: u64) {
+    if let Some(wait) = reserve(bytes) {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// bytes 分を消費し、待つ必要があればその時間を返す
+fn reserve(bytes: u64) -> Option<Duration> {
+    let wait = match LIMITER.lock() {
+        Ok(mut limiter) => limiter.as_mut().map(|b| b.take(bytes)),
+        Err(_) => None,
+    };
+    wait.filter(|w| !w.is_zero())
+}
+
+/// 送信の進捗の通知先と、通知済みのバイト数（リトライで作り直したボディの間で共有する）
+#[derive(Clone)]
+struct SentBytes {
+    reporter: ProgressReporter,
+    reported: Arc<AtomicU64>,
+}
+
+/// 内側のボディのデータを CHUNK_SIZE ごとに区切り、帯域制限に従って待ってから送り出すボディ
+/// ※ sent を指定した場合は、区切りを送り出すたびに進捗を通知する
+struct ThrottledBody {
+    inner: SdkBody,
+    /// 内側から受け取って、まだ送り出していないデータ
+    pending: Bytes,
+    /// 次の区切りを送り出すまでの待ち
+    sleep: Option<Pin<Box<Sleep>>>,
+    sent: Option<SentBytes>,
+    /// このボディで送り出したバイト数
+    position: u64,
+}
+
+impl ThrottledBody {
+    fn new(inner: SdkBody, sent: Option<SentBytes>) -> Self {
+        ThrottledBody {
+            inner,
+            pending: Bytes::new(),
+            sleep: None,
+            sent,
+            position: 0,
+        }
+    }
+
+    fn next_chunk(&mut self) -> Frame<Bytes> {
+        let n = self.pending.len().min(CHUNK_SIZE);
+        self.position += n as u64;
+        if let Some(sent) = &self.sent {
+            let reported = sent.reported.fetch_max(self.position, Ordering::Relaxed);
+            if self.position > reported {
+                sent.reporter.transferred(self.position - reported);
+            }
+        }
+        Frame::data(self.pending.split_to(n))
+    }
+}
+
+impl Body for ThrottledBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(sleep) = &mut this.sleep {
+                ready!(sleep.as_mut().poll(cx));
+                this.sleep = None;
+                return Poll::Ready(Some(Ok(this.next_chunk())));
+            }
+            if !this.pending.is_empty() {
+                let n = this.pending.len().min(CHUNK_SIZE);
+                match reserve(n as u64) {
+                    Some(wait) => this.sleep = Some(Box::pin(tokio::time::sleep(wait))),
+                    None => return Poll::Ready(Some(Ok(this.next_chunk()))),
+                }
+                continue;
+            }
+            match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => this.pending = data,
+                    // トレーラーはそのまま渡す
+                    Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                },
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.pending.is_empty() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let inner = Body::size_hint(&self.inner);
+        let pending = self.pending.len() as u64;
+        let mut hint = SizeHint::new();
+        hint.set_lower(inner.lower() + pending);
+        if let Some(upper) = inner.upper() {
+            hint.set_upper(upper + pending);
+        }
+        hint
+    }
+}
@@ -0,0 +1,258 @@
+use aws_sdk_s3::config::Region;
+use aws_sdk_s3::types::{
+    BucketLocationConstraint, BucketVersioningStatus, CreateBucketConfiguration,
+    VersioningConfiguration,
+};
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+
+use super::delete::{delete_prefix_async, DeleteGuard, DeletePrefixOptions, DeleteReport};
+use super::error::S3Error;
+use super::uri::validate_bucket_name;
+use super::{run_concurrently, run_sync, to_chrono, BoxError};
+
+/// バケットのリージョンを調べる際に同時に実行する GetBucketLocation の数
+const LOCATION_CONCURRENCY: usize = 8;
+
+/// バケットの情報を保持する構造体
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketInfo {
+    /// バケット名
+    pub name: String,
+    /// 作成日時
+    pub creation_date: Option<DateTime<Utc>>,
+    /// バケットが存在するリージョン（GetBucketLocation の結果）
+    /// ※ 権限が無いなどで取得できなかった場合は None
+    pub region: Option<String>,
+}
+
+/// バケット一覧を取得する（非同期版）
+/// ※ ページ取得でエラーがあればそのまま返す
+/// ※ リージョンは並行して調べ、取得できなかったバケットは region を None にする
+pub async fn list_buckets_async(client: &Client) -> Result<Vec<BucketInfo>, BoxError> {
+    let mut bucket_infos = Vec::new();
+    let mut buckets = client.list_buckets().into_paginator().send();
+
+    // PaginationStream から逐次的にページを取得
+    while let Some(page_result) = buckets.next().await {
+        // ページ取得でエラーがあれば即座に返す
+        let page = page_result?;
+        for bucket in page.buckets.unwrap_or_default() {
+            let Some(name) = bucket.name else {
+                continue;
+            };
+            bucket_infos.push(BucketInfo {
+                creation_date: bucket.creation_date.as_ref().and_then(to_chrono),
+                name,
+                region: None,
+            });
+        }
+    }
+
+    let names: Vec<String> = bucket_infos.iter().map(|b| b.name.clone()).collect();
+    let tasks = names.into_iter().enumerate().map(|(i, bucket)| {
+        let client = client.clone();
+        async move {
+            let region = client
+                .get_bucket_location()
+                .bucket(bucket)
+                .send()
+                .await
+                .ok()
+                .map(|location| region_from_location(location.location_constraint()));
+            Ok::<_, BoxError>((i, region))
+        }
+    });
+    run_concurrently(tasks, LOCATION_CONCURRENCY, |(i, region)| {
+        bucket_infos[i].region = region;
+        Ok(())
+    })
+    .await?;
+    Ok(bucket_infos)
+}
+
+/// バケット一覧を取得する
+/// ※ ページ取得でエラーがあればそのまま返す
+/// ※ リージョンを取得できなかったバケットは region を None にする
+pub fn list_buckets() -> Result<Vec<BucketInfo>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { list_buckets_async(&s3).await })
+}
+
+/// バケットを作成する（非同期版）
+/// ※ region を省略した場合はクライアントのリージョンに作成する
+pub async fn create_bucket_async(
+    client: &Client,
+    bucket: &str,
+    region: Option<&str>,
+) -> Result<(), BoxError> {
+    validate_bucket_name(bucket).map_err(|reason| S3Error::InvalidUri {
+        uri: format!("s3://{}", bucket),
+        reason,
+    })?;
+    let region = region
+        .map(str::to_string)
+        .or_else(|| client.config().region().map(|r| r.to_string()))
+        .ok_or("リージョンが指定されていません")?;
+
+    // us-east-1 の場合は LocationConstraint を指定してはいけない
+    let configuration = (region != "us-east-1").then(|| {
+        CreateBucketConfiguration::builder()
+            .location_constraint(BucketLocationConstraint::from(region.as_str()))
+            .build()
+    });
+    client_for_region(client, &region)
+        .create_bucket()
+        .bucket(bucket)
+        .set_create_bucket_configuration(configuration)
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// バケット内のオブジェクトを全バージョン・削除マーカーも含めてすべて削除する（非同期版）
+/// ※ 保護対象のバケットは guard.confirm にバケット名が必要
+pub async fn empty_bucket_async(
+    client: &Client,
+    bucket: &str,
+    guard: &DeleteGuard,
+) -> Result<DeleteReport, BoxError> {
+    let client = client_for_bucket(client, bucket).await?;
+    let options = DeletePrefixOptions {
+        all_versions: true,
+        dry_run: false,
+        guard: guard.clone(),
+    };
+    delete_prefix_async(&client, bucket, "", &options).await
+}
+
+/// バケットを削除する（非同期版）
+/// ※ force を指定すると、先にバケットを空にしてから削除する
+/// ※ 保護対象のバケットは guard.confirm にバケット名が必要
+pub async fn delete_bucket_async(
+    client: &Client,
+    bucket: &str,
+    force: bool,
+    guard: &DeleteGuard,
+) -> Result<(), BoxError> {
+    guard.check(bucket)?;
+    let client = client_for_bucket(client, bucket).await?;
+    if force {
+        let report = empty_bucket_async(&client, bucket, guard).await?;
+        if !report.errors.is_empty() {
+            return Err(format!(
+                "{} 件のオブジェクトを削除できなかったため、バケットを削除できません",
+                report.errors.len()
+            )
+            .into());
+        }
+    }
+    client.delete_bucket().bucket(bucket).send().await?;
+    Ok(())
+}
+
+/// バージョニングを有効化（true）または停止（false）する（非同期版）
+pub async fn set_versioning_async(
+    client: &Client,
+    bucket: &str,
+    enabled: bool,
+) -> Result<(), BoxError> {
+    let status = if enabled {
+        BucketVersioningStatus::Enabled
+    } else {
+        BucketVersioningStatus::Suspended
+    };
+    client
+        .put_bucket_versioning()
+        .bucket(bucket)
+        .versioning_configuration(VersioningConfiguration::builder().status(status).build())
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// バージョニングが有効かどうかを返す（非同期版）
+pub async fn is_versioning_enabled_async(client: &Client, bucket: &str) -> Result<bool, BoxError> {
+    let resp = client.get_bucket_versioning().bucket(bucket).send().await?;
+    Ok(resp.status == Some(BucketVersioningStatus::Enabled))
+}
+
+/// バケットを作成する
+pub fn create_bucket(bucket: &str, region: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { create_bucket_async(&s3, bucket, region).await })
+}
+
+/// バケット内のオブジェクトをすべて削除する
+pub fn empty_bucket(
+    bucket: &str,
+    guard: &DeleteGuard,
+) -> Result<DeleteReport, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { empty_bucket_async(&s3, bucket, guard).await })
+}
+
+/// バケットを削除する
+pub fn delete_bucket(
+    bucket: &str,
+    force: bool,
+    guard: &DeleteGuard,
+) -> Result<(), Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { delete_bucket_async(&s3, bucket, force, guard).await })
+}
+
+/// バージョニングを有効化（true）または停止（false）する
+pub fn set_versioning(bucket: &str, enabled: bool) -> Result<(), Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { set_versioning_async(&s3, bucket, enabled).await })
+}
+
+/// バージョニングが有効かどうかを返す
+pub fn is_versioning_enabled(bucket: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { is_versioning_enabled_async(&s3, bucket).await })
+}
+
+/// バケットが存在するリージョン用のクライアントを返す
+/// ※ クライアントと同じリージョンであれば、そのまま複製して返す
+/// ※ GetBucketLocation はバケットの所有者しか呼べないため、失敗した場合は HeadBucket の
+///   x-amz-bucket-region ヘッダー（エラー応答にも付く）を使い、それも無ければ元のクライアントを使う
+pub(super) async fn client_for_bucket(client: &Client, bucket: &str) -> Result<Client, BoxError> {
+    let region = match client.get_bucket_location().bucket(bucket).send().await {
+        Ok(location) => Some(region_from_location(location.location_constraint())),
+        Err(_) => bucket_region_header(client, bucket).await,
+    };
+    Ok(match region {
+        Some(region) => client_for_region(client, &region),
+        None => client.clone(),
+    })
+}
+
+/// HeadBucket の x-amz-bucket-region ヘッダーからバケットのリージョンを求める
+async fn bucket_region_header(client: &Client, bucket: &str) -> Option<String> {
+    match client.head_bucket().bucket(bucket).send().await {
+        Ok(head) => head.bucket_region,
+        Err(e) => e
+            .raw_response()
+            .and_then(|r| r.headers().get("x-amz-bucket-region"))
+            .map(str::to_string),
+    }
+}
+
+/// 指定したリージョン用のクライアントを返す（認証情報などの設定は引き継ぐ）
+pub(super) fn client_for_region(client: &Client, region: &str) -> Client {
+    if client.config().region().map(|r| r.as_ref()) == Some(region) {
+        return client.clone();
+    }
+    let config = client
+        .config()
+        .to_builder()
+        .region(Region::new(region.to_string()))
+        .build();
+    Client::from_conf(config)
+}
+
+/// GetBucketLocation の LocationConstraint をリージョン名に変換する
+/// ※ 空の場合は us-east-1、旧表記の EU は eu-west-1 を表す
+fn region_from_location(constraint: Option<&BucketLocationConstraint>) -> String {
+    match constraint.map(|c| c.as_str()) {
+        None | Some("") => "us-east-1".to_string(),
+        Some("EU") => "eu-west-1".to_string(),
+        Some(region) => region.to_string(),
+    }
+}
@@ -0,0 +1,309 @@
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use super::download::{copy_body, temp_path, GetOptions};
+use super::encryption::customer_key_headers;
+use super::{run_sync, trim_etag, BoxError};
+
+/// 索引ファイルの名前（キャッシュディレクトリ直下）
+const INDEX_FILE: &str = "index.json";
+
+/// ダウンロードキャッシュのオプション
+#[derive(Debug, Clone)]
+pub struct CacheOptions {
+    /// キャッシュ全体の上限サイズ（バイト）。超えた分は最後に使ってから最も時間が経ったものから削除する
+    pub max_size: u64,
+    /// S3 に問い合わせず、キャッシュにあればそのまま使う（無ければエラー）
+    pub offline: bool,
+    /// S3 に接続できない場合（通信エラー・タイムアウト）は、古い可能性があってもキャッシュを使う
+    pub stale_on_error: bool,
+    /// 取得時のオプション（SSE-C の鍵と進捗の通知先のみ使う）
+    pub get: GetOptions,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        CacheOptions {
+            max_size: 10 * 1024 * 1024 * 1024,
+            offline: false,
+            stale_on_error: false,
+            get: GetOptions::default(),
+        }
+    }
+}
+
+/// キャッシュしているオブジェクトの情報
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub bucket: String,
+    pub key: String,
+    /// キャッシュした時点の ETag（前後のダブルクォートは取り除いた値）
+    pub e_tag: String,
+    pub size: u64,
+    /// 最後に使った日時（LRU の判定に使う）
+    pub last_used: SystemTime,
+    /// キャッシュディレクトリ内のファイル名
+    file: String,
+}
+
+/// 索引（"bucket/key" → エントリ）
+type CacheIndex = BTreeMap<String, CacheEntry>;
+
+/// S3 オブジェクトのローカルキャッシュ
+/// ※ バケット・キー・ETag ごとにファイルを保存し、取得のたびに If-None-Match で変更の有無を確認する
+/// ※ 索引はキャッシュディレクトリの index.json に保存し、プロセスをまたいで再利用する
+/// ※ 同じディレクトリを複数のプロセスから同時に使うことは想定していない
+pub struct S3Cache {
+    dir: PathBuf,
+    options: CacheOptions,
+    index: Mutex<CacheIndex>,
+}
+
+impl S3Cache {
+    /// dir をキャッシュディレクトリとして開く（無ければ作成する）
+    /// ※ ファイルが失われているエントリは索引から取り除く
+    pub fn open(dir: &Path, options: CacheOptions) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(dir)?;
+        let mut index: CacheIndex = match fs::read_to_string(dir.join(INDEX_FILE)) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+            Err(_) => CacheIndex::new(),
+        };
+        index.retain(|_, entry| dir.join(&entry.file).is_file());
+        Ok(S3Cache {
+            dir: dir.to_path_buf(),
+            options,
+            index: Mutex::new(index),
+        })
+    }
+
+    /// オブジェクトのキャッシュファイルのパスを返す（非同期版）
+    /// ※ キャッシュが最新なら S3 からはダウンロードしない（304 Not Modified）
+    /// ※ 返したファイルは、後の取得でキャッシュから追い出されると削除される
+    pub async fn get_path_async(
+        &self,
+        client: &Client,
+        bucket: &str,
+        key: &str,
+    ) -> Result<PathBuf, BoxError> {
+        let id = entry_id(bucket, key);
+        let cached = self.lock()?.get(&id).cloned();
+        if self.options.offline {
+            let entry = cached.ok_or_else(|| {
+                format!(
+                    "オフラインのため取得できません（キャッシュにありません）: s3://{}/{}",
+                    bucket, key
+                )
+            })?;
+            return self.touch(&id, &entry);
+        }
+
+        let customer = customer_key_headers(self.options.get.encryption.as_ref())?;
+        let result = client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .set_if_none_match(cached.as_ref().map(|e| format!("\"{}\"", e.e_tag)))
+            .set_sse_customer_algorithm(customer.algorithm)
+            .set_sse_customer_key(customer.key)
+            .set_sse_customer_key_md5(customer.key_md5)
+            .send()
+            .await;
+        let resp = match (result, cached) {
+            (Ok(resp), _) => resp,
+            (Err(e), Some(entry)) => {
+                let not_modified = e.raw_response().is_some_and(|r| r.status().as_u16() == 304);
+                let unreachable =
+                    matches!(e, SdkError::DispatchFailure(_) | SdkError::TimeoutError(_));
+                if not_modified || (unreachable && self.options.stale_on_error) {
+                    return self.touch(&id, &entry);
+                }
+                return Err(e.into());
+            }
+            (Err(e), None) => return Err(e.into()),
+        };
+
+        let e_tag = resp
+            .e_tag
+            .as_deref()
+            .map(trim_etag)
+            .ok_or("ETag が返されていません")?;
+        let file = format!("{}-{}", id_hash(&id), sanitize(&e_tag));
+        let path = self.dir.join(&file);
+        let tmp = temp_path(&path)?;
+        let result: Result<u64, BoxError> = async {
+            let mut out = File::create(&tmp)?;
+            let info = copy_body(
+                key,
+                resp,
+                &mut out,
+                None,
+                self.options.get.progress.as_ref(),
+            )
+            .await?;
+            out.sync_all()?;
+            drop(out);
+            fs::rename(&tmp, &path)?;
+            Ok(info.size)
+        }
+        .await;
+        let size = match result {
+            Ok(size) => size,
+            Err(e) => {
+                let _ = fs::remove_file(&tmp);
+                return Err(e);
+            }
+        };
+
+        let entry = CacheEntry {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            e_tag,
+            size,
+            last_used: SystemTime::now(),
+            file,
+        };
+        let mut index = self.lock()?;
+        if let Some(old) = index.insert(id.clone(), entry) {
+            if self.dir.join(&old.file) != path {
+                let _ = fs::remove_file(self.dir.join(&old.file));
+            }
+        }
+        self.evict(&mut index, &id);
+        self.save(&index)?;
+        Ok(path)
+    }
+
+    /// オブジェクトの内容をキャッシュ経由で取得する（非同期版）
+    pub async fn get_bytes_async(
+        &self,
+        client: &Client,
+        bucket: &str,
+        key: &str,
+    ) -> Result<Vec<u8>, BoxError> {
+        let path = self.get_path_async(client, bucket, key).await?;
+        Ok(fs::read(path)?)
+    }
+
+    /// オブジェクトのキャッシュファイルのパスを返す
+    pub fn get_path(&self, bucket: &str, key: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        run_sync(|s3| async move { self.get_path_async(&s3, bucket, key).await })
+    }
+
+    /// オブジェクトの内容をキャッシュ経由で取得する
+    pub fn get_bytes(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        run_sync(|s3| async move { self.get_bytes_async(&s3, bucket, key).await })
+    }
+
+    /// キャッシュしているオブジェクトの一覧（最後に使った日時の新しい順）
+    pub fn entries(&self) -> Vec<CacheEntry> {
+        let mut entries: Vec<CacheEntry> = match self.index.lock() {
+            Ok(index) => index.values().cloned().collect(),
+            Err(_) => Vec::new(),
+        };
+        entries.sort_by_key(|e| Reverse(e.last_used));
+        entries
+    }
+
+    /// キャッシュの合計サイズ（バイト）
+    pub fn size(&self) -> u64 {
+        self.entries().iter().map(|e| e.size).sum()
+    }
+
+    /// キャッシュからオブジェクトを取り除く
+    pub fn remove(&self, bucket: &str, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let id = entry_id(bucket, key);
+        self.update(|index| index.remove(&id).into_iter().collect())
+    }
+
+    /// キャッシュをすべて削除する
+    pub fn clear(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.update(|index| std::mem::take(index).into_values().collect())
+    }
+
+    /// 索引から取り除いたエントリのファイルを削除し、索引を保存する
+    fn update(
+        &self,
+        f: impl FnOnce(&mut CacheIndex) -> Vec<CacheEntry>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.lock().and_then(|mut index| {
+            for entry in f(&mut index) {
+                let _ = fs::remove_file(self.dir.join(entry.file));
+            }
+            self.save(&index)
+        });
+        result.map_err(|e| e as Box<dyn std::error::Error>)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, CacheIndex>, BoxError> {
+        self.index
+            .lock()
+            .map_err(|_| "キャッシュの索引のロックに失敗しました".into())
+    }
+
+    /// 最後に使った日時を更新して、キャッシュファイルのパスを返す
+    fn touch(&self, id: &str, entry: &CacheEntry) -> Result<PathBuf, BoxError> {
+        let mut index = self.lock()?;
+        if let Some(entry) = index.get_mut(id) {
+            entry.last_used = SystemTime::now();
+        }
+        self.save(&index)?;
+        Ok(self.dir.join(&entry.file))
+    }
+
+    /// 上限サイズを超えている間、最後に使ってから最も時間が経ったエントリを削除する
+    /// ※ keep のエントリ（今回取得したもの）は、それ自体が上限を超えていても残す
+    fn evict(&self, index: &mut CacheIndex, keep: &str) {
+        let mut total: u64 = index.values().map(|e| e.size).sum();
+        while total > self.options.max_size {
+            let oldest = index
+                .iter()
+                .filter(|(id, _)| id.as_str() != keep)
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(id, _)| id.clone());
+            let Some(entry) = oldest.and_then(|id| index.remove(&id)) else {
+                break;
+            };
+            let _ = fs::remove_file(self.dir.join(&entry.file));
+            total -= entry.size;
+        }
+    }
+
+    /// 索引を保存する（一時ファイルに書き込んでからリネームする）
+    fn save(&self, index: &CacheIndex) -> Result<(), BoxError> {
+        let path = self.dir.join(INDEX_FILE);
+        let tmp = temp_path(&path)?;
+        fs::write(&tmp, serde_json::to_string_pretty(index)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// 索引のキー
+fn entry_id(bucket: &str, key: &str) -> String {
+    format!("{}/{}", bucket, key)
+}
+
+/// キャッシュファイル名に使う、索引のキーのハッシュ
+fn id_hash(id: &str) -> String {
+    format!("{:x}", Sha256::digest(id.as_bytes()))
+}
+
+/// ETag をファイル名に使える文字だけにする
+fn sanitize(e_tag: &str) -> String {
+    e_tag
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,306 @@
+use aws_sdk_s3::types::{ChecksumAlgorithm as SdkChecksumAlgorithm, ChecksumMode};
+use aws_sdk_s3::Client;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use super::encryption::CustomerKeyHeaders;
+use super::error::S3Error;
+use super::BoxError;
+
+/// 転送時に計算・検証するチェックサムのアルゴリズム
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChecksumAlgorithm {
+    Crc32c,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// SDK のアルゴリズムに変換する
+    pub(super) fn to_sdk(self) -> SdkChecksumAlgorithm {
+        match self {
+            ChecksumAlgorithm::Crc32c => SdkChecksumAlgorithm::Crc32C,
+            ChecksumAlgorithm::Sha256 => SdkChecksumAlgorithm::Sha256,
+        }
+    }
+
+    /// バイト列のチェックサムを計算する（Base64 エンコードした値）
+    pub fn checksum(self, data: &[u8]) -> String {
+        let mut hasher = Hasher::new(self);
+        hasher.update(data);
+        BASE64.encode(hasher.finalize())
+    }
+
+    /// レスポンスに含まれるチェックサムのうち、このアルゴリズムの値を選ぶ
+    pub(super) fn pick<'a>(
+        self,
+        crc32c: Option<&'a str>,
+        sha256: Option<&'a str>,
+    ) -> Option<&'a str> {
+        match self {
+            ChecksumAlgorithm::Crc32c => crc32c,
+            ChecksumAlgorithm::Sha256 => sha256,
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumAlgorithm::Crc32c => write!(f, "CRC32C"),
+            ChecksumAlgorithm::Sha256 => write!(f, "SHA256"),
+        }
+    }
+}
+
+/// リクエストに設定するチェックサムのヘッダー
+#[derive(Debug, Default)]
+pub(super) struct ChecksumHeaders {
+    pub(super) crc32c: Option<String>,
+    pub(super) sha256: Option<String>,
+}
+
+/// 計算したチェックサムをリクエストヘッダーの値に変換する
+pub(super) fn checksum_headers(checksum: Option<&(ChecksumAlgorithm, String)>) -> ChecksumHeaders {
+    match checksum {
+        None => ChecksumHeaders::default(),
+        Some((ChecksumAlgorithm::Crc32c, value)) => ChecksumHeaders {
+            crc32c: Some(value.clone()),
+            sha256: None,
+        },
+        Some((ChecksumAlgorithm::Sha256, value)) => ChecksumHeaders {
+            crc32c: None,
+            sha256: Some(value.clone()),
+        },
+    }
+}
+
+/// アルゴリズムごとの計算途中の状態
+enum Hasher {
+    Crc32c(u32),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Crc32c => Hasher::Crc32c(0),
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Crc32c(crc) => *crc = crc32c::crc32c_append(*crc, data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    /// 生のチェックサム（CRC32C はビッグエンディアンの 4 バイト）
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Crc32c(crc) => crc.to_be_bytes().to_vec(),
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+/// データを順に受け取ってチェックサムを計算する
+/// ※ part_size を指定した場合は、マルチパートの複合チェックサム（各パートのチェックサムを連結したもののチェックサム + "-N"）
+pub(super) struct ChecksumCalculator {
+    algorithm: ChecksumAlgorithm,
+    part_size: Option<u64>,
+    current: Hasher,
+    in_part: u64,
+    part_checksums: Vec<u8>,
+    parts: usize,
+}
+
+impl ChecksumCalculator {
+    pub(super) fn new(algorithm: ChecksumAlgorithm, part_size: Option<u64>) -> Self {
+        ChecksumCalculator {
+            algorithm,
+            part_size: part_size.filter(|&size| size > 0),
+            current: Hasher::new(algorithm),
+            in_part: 0,
+            part_checksums: Vec::new(),
+            parts: 0,
+        }
+    }
+
+    pub(super) fn update(&mut self, mut data: &[u8]) {
+        let Some(part_size) = self.part_size else {
+            self.current.update(data);
+            return;
+        };
+        while !data.is_empty() {
+            let take = ((part_size - self.in_part) as usize).min(data.len());
+            self.current.update(&data[..take]);
+            self.in_part += take as u64;
+            data = &data[take..];
+            if self.in_part == part_size {
+                self.finish_part();
+            }
+        }
+    }
+
+    fn finish_part(&mut self) {
+        let hasher = std::mem::replace(&mut self.current, Hasher::new(self.algorithm));
+        self.part_checksums.extend(hasher.finalize());
+        self.in_part = 0;
+        self.parts += 1;
+    }
+
+    /// Base64 エンコードしたチェックサムを返す
+    pub(super) fn finish(mut self) -> String {
+        if self.part_size.is_none() {
+            return BASE64.encode(self.current.finalize());
+        }
+        if self.in_part > 0 {
+            self.finish_part();
+        }
+        composite_checksum(self.algorithm, &self.part_checksums, self.parts)
+    }
+}
+
+/// 生のパートチェックサムを連結したものから複合チェックサムを求める
+fn composite_checksum(algorithm: ChecksumAlgorithm, part_checksums: &[u8], parts: usize) -> String {
+    let mut hasher = Hasher::new(algorithm);
+    hasher.update(part_checksums);
+    format!("{}-{}", BASE64.encode(hasher.finalize()), parts)
+}
+
+/// Base64 エンコードされたパートのチェックサムから複合チェックサムを求める
+pub(super) fn composite_from_parts(
+    algorithm: ChecksumAlgorithm,
+    parts: &[String],
+) -> Result<String, BoxError> {
+    let mut raw = Vec::new();
+    for part in parts {
+        raw.extend(BASE64.decode(part)?);
+    }
+    Ok(composite_checksum(algorithm, &raw, parts.len()))
+}
+
+/// 複合チェックサム（"値-N"）の場合はパート数を返す
+pub(super) fn composite_parts(checksum: &str) -> Option<u64> {
+    checksum.rsplit_once('-')?.1.parse().ok()
+}
+
+/// ファイルのチェックサムを計算する
+pub(super) fn file_checksum(
+    path: &Path,
+    algorithm: ChecksumAlgorithm,
+    part_size: Option<u64>,
+) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut calculator = ChecksumCalculator::new(algorithm, part_size);
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        calculator.update(&buf[..n]);
+    }
+    Ok(calculator.finish())
+}
+
+/// ダウンロードしたデータと照合する、S3 に保存されているチェックサム
+#[derive(Debug, Clone)]
+pub(super) struct ExpectedChecksum {
+    pub(super) algorithm: ChecksumAlgorithm,
+    pub(super) value: String,
+    /// 複合チェックサムの場合のパートサイズ
+    pub(super) part_size: Option<u64>,
+}
+
+impl ExpectedChecksum {
+    /// 同じ方法でチェックサムを計算する計算器を返す
+    pub(super) fn calculator(&self) -> ChecksumCalculator {
+        ChecksumCalculator::new(self.algorithm, self.part_size)
+    }
+
+    /// 計算したチェックサムと照合する
+    /// ※ 複合チェックサムは 1 パート目の大きさからパートの境界を推定して計算するため、
+    ///   一致しない場合はパートサイズが不揃いなのか内容が壊れているのかを区別できない。
+    ///   その場合は S3Error::ChecksumUnverifiable を返す
+    pub(super) fn verify(&self, key: &str, actual: &str) -> Result<(), BoxError> {
+        if let Some(expected_parts) = composite_parts(&self.value) {
+            if actual != self.value {
+                return Err(S3Error::ChecksumUnverifiable {
+                    key: key.to_string(),
+                    algorithm: self.algorithm.to_string(),
+                    expected_parts,
+                    actual_parts: composite_parts(actual).unwrap_or(1),
+                }
+                .into());
+            }
+        }
+        verify_checksum(key, self.algorithm, &self.value, actual)
+    }
+}
+
+/// S3 が返したチェックサムから、照合に使う値を決める
+/// ※ 複合チェックサムの場合は、1 パート目の HeadObject からパートサイズを求める
+/// ※ オブジェクトに指定したアルゴリズムのチェックサムが無い場合は None（検証しない）
+pub(super) async fn expected_checksum(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    version_id: Option<&str>,
+    customer: &CustomerKeyHeaders,
+    algorithm: ChecksumAlgorithm,
+    value: Option<&str>,
+) -> Result<Option<ExpectedChecksum>, BoxError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let part_size = match composite_parts(value) {
+        None => None,
+        Some(_) => {
+            let first_part = client
+                .head_object()
+                .bucket(bucket)
+                .key(key)
+                .set_version_id(version_id.map(str::to_string))
+                .part_number(1)
+                .checksum_mode(ChecksumMode::Enabled)
+                .set_sse_customer_algorithm(customer.algorithm.clone())
+                .set_sse_customer_key(customer.key.clone())
+                .set_sse_customer_key_md5(customer.key_md5.clone())
+                .send()
+                .await?;
+            Some(first_part.content_length.unwrap_or(0).max(0) as u64)
+        }
+    };
+    Ok(Some(ExpectedChecksum {
+        algorithm,
+        value: value.to_string(),
+        part_size,
+    }))
+}
+
+/// 計算したチェックサムが期待値と一致するか検証する
+pub(super) fn verify_checksum(
+    key: &str,
+    algorithm: ChecksumAlgorithm,
+    expected: &str,
+    actual: &str,
+) -> Result<(), BoxError> {
+    if expected != actual {
+        return Err(S3Error::ChecksumMismatch {
+            key: key.to_string(),
+            algorithm: algorithm.to_string(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use flate2::write::{GzDecoder, GzEncoder};
+use std::io::{self, Write};
+
+/// オブジェクトの圧縮形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip（Content-Encoding: gzip、拡張子 .gz）
+    Gzip,
+    /// Zstandard（Content-Encoding: zstd、拡張子 .zst）
+    Zstd,
+}
+
+impl Compression {
+    /// Content-Encoding に設定する値
+    pub fn content_encoding(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// キーに付ける拡張子
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+
+    /// Content-Encoding、次いでキーの拡張子から圧縮形式を判定する
+    pub fn detect(content_encoding: Option<&str>, key: &str) -> Option<Self> {
+        const ALL: [Compression; 2] = [Compression::Gzip, Compression::Zstd];
+        let from_encoding = content_encoding.and_then(|encoding| {
+            encoding.split(',').find_map(|e| {
+                ALL.into_iter()
+                    .find(|c| e.trim().eq_ignore_ascii_case(c.content_encoding()))
+            })
+        });
+        from_encoding.or_else(|| ALL.into_iter().find(|c| key.ends_with(c.extension())))
+    }
+
+    /// 圧縮したオブジェクトのキー（拡張子が付いていなければ付け足す）
+    pub fn key_for(self, key: &str) -> String {
+        if key.ends_with(self.extension()) {
+            key.to_string()
+        } else {
+            format!("{}{}", key, self.extension())
+        }
+    }
+
+    /// バイト列を圧縮する
+    pub(super) fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut encoder = EncodingWriter::new(self, Vec::new())?;
+        encoder.write_all(data)?;
+        encoder.finish()
+    }
+}
+
+/// 書き込まれたデータを圧縮して内側の writer に書き込む
+/// ※ finish() を呼ぶまで末尾のデータは書き込まれない
+pub(super) enum EncodingWriter<W: Write> {
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+}
+
+impl<W: Write> EncodingWriter<W> {
+    pub(super) fn new(compression: Compression, writer: W) -> io::Result<Self> {
+        Ok(match compression {
+            Compression::Gzip => {
+                EncodingWriter::Gzip(GzEncoder::new(writer, flate2::Compression::default()))
+            }
+            Compression::Zstd => EncodingWriter::Zstd(zstd::stream::write::Encoder::new(
+                writer,
+                zstd::DEFAULT_COMPRESSION_LEVEL,
+            )?),
+        })
+    }
+
+    pub(super) fn get_mut(&mut self) -> &mut W {
+        match self {
+            EncodingWriter::Gzip(encoder) => encoder.get_mut(),
+            EncodingWriter::Zstd(encoder) => encoder.get_mut(),
+        }
+    }
+
+    /// 残りのデータを書き込んで内側の writer を返す
+    pub(super) fn finish(self) -> io::Result<W> {
+        match self {
+            EncodingWriter::Gzip(encoder) => encoder.finish(),
+            EncodingWriter::Zstd(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for EncodingWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self {
+            EncodingWriter::Gzip(encoder) => encoder.write(data),
+            EncodingWriter::Zstd(encoder) => encoder.write(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            EncodingWriter::Gzip(encoder) => encoder.flush(),
+            EncodingWriter::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// 書き込まれたデータを展開して内側の writer に書き込む（圧縮なしの場合はそのまま書き込む）
+/// ※ finish() で末尾のデータを書き込む（gzip はデータが途中で切れていないことも確認する）
+pub(super) enum DecodingWriter<W: Write> {
+    Plain(W),
+    Gzip(GzDecoder<W>),
+    Zstd(zstd::stream::write::Decoder<'static, W>),
+}
+
+impl<W: Write> DecodingWriter<W> {
+    pub(super) fn new(compression: Option<Compression>, writer: W) -> io::Result<Self> {
+        Ok(match compression {
+            None => DecodingWriter::Plain(writer),
+            Some(Compression::Gzip) => DecodingWriter::Gzip(GzDecoder::new(writer)),
+            Some(Compression::Zstd) => {
+                DecodingWriter::Zstd(zstd::stream::write::Decoder::new(writer)?)
+            }
+        })
+    }
+
+    /// 残りのデータを書き込んで内側の writer を返す
+    pub(super) fn finish(self) -> io::Result<W> {
+        match self {
+            DecodingWriter::Plain(writer) => Ok(writer),
+            DecodingWriter::Gzip(decoder) => decoder.finish(),
+            DecodingWriter::Zstd(mut decoder) => {
+                decoder.flush()?;
+                Ok(decoder.into_inner())
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for DecodingWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self {
+            DecodingWriter::Plain(writer) => writer.write(data),
+            DecodingWriter::Gzip(decoder) => decoder.write(data),
+            DecodingWriter::Zstd(decoder) => decoder.write(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            DecodingWriter::Plain(writer) => writer.flush(),
+            DecodingWriter::Gzip(decoder) => decoder.flush(),
+            DecodingWriter::Zstd(decoder) => decoder.flush(),
+        }
+    }
+}
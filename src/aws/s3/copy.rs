@@ -0,0 +1,577 @@
+use aws_sdk_s3::operation::head_object::HeadObjectOutput;
+use aws_sdk_s3::types::MetadataDirective;
+use aws_sdk_s3::Client;
+
+use super::bucket::client_for_bucket;
+use super::delete::{delete_object_async, DeleteGuard};
+use super::encryption::{
+    customer_key_headers, sse_headers, CustomerKeyHeaders, Encryption, EncryptionInfo,
+};
+use super::list::list_stream_matching;
+use super::multipart::{complete_upload, create_upload, effective_part_size, PartState};
+use super::pattern::KeyPattern;
+use super::progress::ProgressReporter;
+use super::upload::{PutOptions, PutResult};
+use super::uri::percent_encode_key;
+use super::{run_concurrently, run_sync, trim_etag, BoxError};
+
+/// CopyObject で一度にコピーできるサイズの上限（これを超えると UploadPartCopy を使う）
+pub const COPY_OBJECT_LIMIT: u64 = 5 * 1024 * 1024 * 1024;
+
+/// コピーのオプション
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    /// 指定するとメタデータなどの属性を置き換える（未指定ならコピー元の属性を引き継ぐ）
+    pub replace: Option<PutOptions>,
+    /// UploadPartCopy で使うパートサイズ（バイト）
+    /// ※ パート数が 10,000 を超える場合は自動的に大きくする
+    pub part_size: u64,
+    /// 同時に実行するコピー（パート、またはプレフィックス配下のオブジェクト）の数
+    pub concurrency: usize,
+    /// コピー先のサーバー側暗号化（None の場合は replace の指定、次いでバケットの既定に従う）
+    /// ※ コピー元の暗号化は引き継がれない
+    pub encryption: Option<Encryption>,
+    /// コピー元が SSE-C で暗号化されている場合の鍵
+    pub source_encryption: Option<Encryption>,
+    /// 移動でコピー元を削除する際の、保護対象のバケットの確認
+    pub guard: DeleteGuard,
+    /// 進捗の通知先
+    /// ※ サーバー側でコピーするため、CopyObject は完了時にまとめて、UploadPartCopy はパートごとに通知する
+    pub progress: Option<ProgressReporter>,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        CopyOptions {
+            replace: None,
+            part_size: 512 * 1024 * 1024,
+            concurrency: 8,
+            encryption: None,
+            source_encryption: None,
+            guard: DeleteGuard::default(),
+            progress: None,
+        }
+    }
+}
+
+impl CopyOptions {
+    /// コピー先に適用する暗号化
+    fn dest_encryption(&self) -> Option<&Encryption> {
+        self.encryption.as_ref().or_else(|| {
+            self.replace
+                .as_ref()
+                .and_then(|replace| replace.encryption.as_ref())
+        })
+    }
+}
+
+/// オブジェクトをサーバー側でコピーする（非同期版）
+/// ※ バケットが別リージョンにあっても、それぞれのリージョンに合わせて実行する
+pub async fn copy_object_async(
+    client: &Client,
+    src_bucket: &str,
+    src_key: &str,
+    dst_bucket: &str,
+    dst_key: &str,
+    options: &CopyOptions,
+) -> Result<PutResult, BoxError> {
+    let src_client = client_for_bucket(client, src_bucket).await?;
+    let dst_client = client_for_bucket(client, dst_bucket).await?;
+    copy_between(
+        &src_client,
+        &dst_client,
+        &ObjectRef::new(src_bucket, src_key),
+        &ObjectRef::new(dst_bucket, dst_key),
+        options,
+    )
+    .await
+}
+
+/// オブジェクトを移動する（コピー後にコピー元を削除する）（非同期版）
+/// ※ コピー元とコピー先が同じ場合はエラーにする（コピー後の削除でオブジェクトが失われるため）
+pub async fn move_object_async(
+    client: &Client,
+    src_bucket: &str,
+    src_key: &str,
+    dst_bucket: &str,
+    dst_key: &str,
+    options: &CopyOptions,
+) -> Result<PutResult, BoxError> {
+    if src_bucket == dst_bucket && src_key == dst_key {
+        return Err(format!("移動元と移動先が同じです: s3://{}/{}", src_bucket, src_key).into());
+    }
+    options.guard.check(src_bucket)?;
+    let src_client = client_for_bucket(client, src_bucket).await?;
+    let dst_client = client_for_bucket(client, dst_bucket).await?;
+    let src = ObjectRef::new(src_bucket, src_key);
+    let result = copy_between(
+        &src_client,
+        &dst_client,
+        &src,
+        &ObjectRef::new(dst_bucket, dst_key),
+        options,
+    )
+    .await?;
+    delete_object_async(&src_client, &src.bucket, &src.key, &options.guard).await?;
+    Ok(result)
+}
+
+/// プレフィックス配下のオブジェクトをまとめてコピーする（非同期版）
+/// ※ コピー先のキーは「dst_prefix + コピー元プレフィックスからの相対キー」になる
+/// ※ 戻り値はコピー先のキーの一覧
+pub async fn copy_prefix_async(
+    client: &Client,
+    src_bucket: &str,
+    src_prefix: &str,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+) -> Result<Vec<String>, BoxError> {
+    let src = KeyPattern::under(src_prefix);
+    transfer_matching(
+        client, src_bucket, &src, dst_bucket, dst_prefix, options, false,
+    )
+    .await
+}
+
+/// プレフィックス配下のオブジェクトをまとめて移動する（非同期版）
+pub async fn move_prefix_async(
+    client: &Client,
+    src_bucket: &str,
+    src_prefix: &str,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+) -> Result<Vec<String>, BoxError> {
+    let src = KeyPattern::under(src_prefix);
+    transfer_matching(
+        client, src_bucket, &src, dst_bucket, dst_prefix, options, true,
+    )
+    .await
+}
+
+/// パターンに一致するオブジェクトをまとめてコピーする（非同期版）
+/// ※ コピー先のキーは、パターンの起点（KeyPattern::base）からの相対キーを dst_prefix に付けたもの
+/// 例: "logs/2026-*/**/*.json" を "archive/" にコピーすると logs/2026-01/a.json は archive/2026-01/a.json になる
+pub async fn copy_matching_async(
+    client: &Client,
+    src_bucket: &str,
+    src: &KeyPattern,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+) -> Result<Vec<String>, BoxError> {
+    transfer_matching(
+        client, src_bucket, src, dst_bucket, dst_prefix, options, false,
+    )
+    .await
+}
+
+/// パターンに一致するオブジェクトをまとめて移動する（非同期版）
+pub async fn move_matching_async(
+    client: &Client,
+    src_bucket: &str,
+    src: &KeyPattern,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+) -> Result<Vec<String>, BoxError> {
+    transfer_matching(
+        client, src_bucket, src, dst_bucket, dst_prefix, options, true,
+    )
+    .await
+}
+
+/// オブジェクトをサーバー側でコピーする
+pub fn copy_object(
+    src_bucket: &str,
+    src_key: &str,
+    dst_bucket: &str,
+    dst_key: &str,
+    options: &CopyOptions,
+) -> Result<PutResult, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move {
+        copy_object_async(&s3, src_bucket, src_key, dst_bucket, dst_key, options).await
+    })
+}
+
+/// オブジェクトを移動する
+pub fn move_object(
+    src_bucket: &str,
+    src_key: &str,
+    dst_bucket: &str,
+    dst_key: &str,
+    options: &CopyOptions,
+) -> Result<PutResult, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move {
+        move_object_async(&s3, src_bucket, src_key, dst_bucket, dst_key, options).await
+    })
+}
+
+/// プレフィックス配下のオブジェクトをまとめてコピーする
+pub fn copy_prefix(
+    src_bucket: &str,
+    src_prefix: &str,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move {
+        copy_prefix_async(&s3, src_bucket, src_prefix, dst_bucket, dst_prefix, options).await
+    })
+}
+
+/// プレフィックス配下のオブジェクトをまとめて移動する
+pub fn move_prefix(
+    src_bucket: &str,
+    src_prefix: &str,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move {
+        move_prefix_async(&s3, src_bucket, src_prefix, dst_bucket, dst_prefix, options).await
+    })
+}
+
+/// パターンに一致するオブジェクトをまとめてコピーする
+pub fn copy_matching(
+    src_bucket: &str,
+    src: &KeyPattern,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move {
+        copy_matching_async(&s3, src_bucket, src, dst_bucket, dst_prefix, options).await
+    })
+}
+
+/// パターンに一致するオブジェクトをまとめて移動する
+pub fn move_matching(
+    src_bucket: &str,
+    src: &KeyPattern,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move {
+        move_matching_async(&s3, src_bucket, src, dst_bucket, dst_prefix, options).await
+    })
+}
+
+/// 過去のバージョンを同じキーの最新バージョンとしてコピーし直す
+pub(super) async fn copy_version(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    version_id: &str,
+    options: &CopyOptions,
+) -> Result<PutResult, BoxError> {
+    let src = ObjectRef {
+        version_id: Some(version_id.to_string()),
+        ..ObjectRef::new(bucket, key)
+    };
+    copy_between(client, client, &src, &ObjectRef::new(bucket, key), options).await
+}
+
+/// バケットとキーの組
+#[derive(Debug, Clone)]
+struct ObjectRef {
+    bucket: String,
+    key: String,
+    /// コピー元の場合のみ指定できるバージョン ID（None なら最新）
+    version_id: Option<String>,
+}
+
+impl ObjectRef {
+    fn new(bucket: &str, key: &str) -> Self {
+        ObjectRef {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            version_id: None,
+        }
+    }
+
+    /// x-amz-copy-source ヘッダーの値（キーは URL エンコードする）
+    fn copy_source(&self) -> String {
+        let source = format!("{}/{}", self.bucket, percent_encode_key(&self.key));
+        match &self.version_id {
+            Some(version_id) => format!("{}?versionId={}", source, version_id),
+            None => source,
+        }
+    }
+}
+
+/// パターンに一致するオブジェクトを並行してコピー（移動）する
+async fn transfer_matching(
+    client: &Client,
+    src_bucket: &str,
+    src: &KeyPattern,
+    dst_bucket: &str,
+    dst_prefix: &str,
+    options: &CopyOptions,
+    remove_source: bool,
+) -> Result<Vec<String>, BoxError> {
+    if remove_source {
+        if src_bucket == dst_bucket && src.base() == dst_prefix {
+            return Err(format!(
+                "移動元と移動先が同じです: s3://{}/{}",
+                src_bucket, dst_prefix
+            )
+            .into());
+        }
+        options.guard.check(src_bucket)?;
+    }
+    let src_client = client_for_bucket(client, src_bucket).await?;
+    let dst_client = client_for_bucket(client, dst_bucket).await?;
+
+    let mut pairs = Vec::new();
+    let mut stream = list_stream_matching(&src_client, src_bucket, src);
+    while let Some(object) = stream.next_object().await {
+        let object = object?;
+        let rel = object.key.strip_prefix(src.base()).unwrap_or(&object.key);
+        let dst_key = format!("{}{}", dst_prefix, rel);
+        pairs.push((
+            ObjectRef::new(src_bucket, &object.key),
+            ObjectRef::new(dst_bucket, &dst_key),
+        ));
+    }
+
+    // 1 オブジェクトずつ並行してコピーするので、パートの並行数は 1 にする
+    let object_options = CopyOptions {
+        concurrency: 1,
+        ..options.clone()
+    };
+    let tasks = pairs.into_iter().map(|(src, dst)| {
+        let (src_client, dst_client) = (src_client.clone(), dst_client.clone());
+        let object_options = object_options.clone();
+        async move {
+            copy_between(&src_client, &dst_client, &src, &dst, &object_options).await?;
+            if remove_source {
+                let guard = &object_options.guard;
+                delete_object_async(&src_client, &src.bucket, &src.key, guard).await?;
+            }
+            Ok(dst.key)
+        }
+    });
+
+    let mut copied = Vec::new();
+    run_concurrently(tasks, options.concurrency, |key| {
+        copied.push(key);
+        Ok(())
+    })
+    .await?;
+    copied.sort();
+    Ok(copied)
+}
+
+/// リージョンごとのクライアントを使ってコピーする
+/// ※ COPY_OBJECT_LIMIT を超えるオブジェクトは UploadPartCopy で分割してコピーする
+async fn copy_between(
+    src_client: &Client,
+    dst_client: &Client,
+    src: &ObjectRef,
+    dst: &ObjectRef,
+    options: &CopyOptions,
+) -> Result<PutResult, BoxError> {
+    let source = customer_key_headers(options.source_encryption.as_ref())?;
+    let head = src_client
+        .head_object()
+        .bucket(&src.bucket)
+        .key(&src.key)
+        .set_version_id(src.version_id.clone())
+        .set_sse_customer_algorithm(source.algorithm.clone())
+        .set_sse_customer_key(source.key.clone())
+        .set_sse_customer_key_md5(source.key_md5.clone())
+        .send()
+        .await?;
+    let size = head.content_length.unwrap_or(0).max(0) as u64;
+    // HeadObject で確認した世代からコピーする（途中で上書きされた場合は 412 で失敗させる）
+    let src = &ObjectRef {
+        version_id: src.version_id.clone().or_else(|| head.version_id.clone()),
+        ..src.clone()
+    };
+    if let Some(progress) = &options.progress {
+        progress.file_started(&dst.key, size);
+    }
+    let result = if size > COPY_OBJECT_LIMIT {
+        multipart_copy(dst_client, src, dst, &head, size, options).await
+    } else {
+        single_copy(dst_client, src, dst, &head, size, &source, options).await
+    };
+    if let (Ok(_), Some(progress)) = (&result, &options.progress) {
+        progress.file_finished(&dst.key);
+    }
+    result
+}
+
+/// CopyObject で 1 回でコピーする
+async fn single_copy(
+    dst_client: &Client,
+    src: &ObjectRef,
+    dst: &ObjectRef,
+    head: &HeadObjectOutput,
+    size: u64,
+    source: &CustomerKeyHeaders,
+    options: &CopyOptions,
+) -> Result<PutResult, BoxError> {
+    let sse = sse_headers(options.dest_encryption())?;
+    let mut request = dst_client
+        .copy_object()
+        .copy_source(src.copy_source())
+        .set_copy_source_if_match(head.e_tag.clone())
+        .bucket(&dst.bucket)
+        .key(&dst.key)
+        .set_server_side_encryption(sse.server_side_encryption)
+        .set_ssekms_key_id(sse.kms_key_id)
+        .set_ssekms_encryption_context(sse.kms_context)
+        .set_sse_customer_algorithm(sse.customer.algorithm)
+        .set_sse_customer_key(sse.customer.key)
+        .set_sse_customer_key_md5(sse.customer.key_md5)
+        .set_copy_source_sse_customer_algorithm(source.algorithm.clone())
+        .set_copy_source_sse_customer_key(source.key.clone())
+        .set_copy_source_sse_customer_key_md5(source.key_md5.clone());
+    if let Some(replace) = &options.replace {
+        let metadata = (!replace.metadata.is_empty()).then(|| replace.metadata.clone());
+        request = request
+            .metadata_directive(MetadataDirective::Replace)
+            .set_content_type(replace.content_type.clone())
+            .set_metadata(metadata)
+            .set_cache_control(replace.cache_control.clone())
+            .set_content_encoding(replace.content_encoding.clone())
+            .set_storage_class(replace.storage_class.clone())
+            .set_acl(replace.acl.clone());
+    }
+    let resp = request.send().await?;
+    if let Some(progress) = &options.progress {
+        progress.transferred(size);
+    }
+    Ok(PutResult {
+        e_tag: resp
+            .copy_object_result
+            .and_then(|r| r.e_tag)
+            .as_deref()
+            .map(trim_etag),
+        version_id: resp.version_id,
+        encryption: EncryptionInfo::from_headers(
+            resp.server_side_encryption.as_ref(),
+            resp.ssekms_key_id.as_deref(),
+            resp.sse_customer_algorithm.as_deref(),
+        ),
+        checksum: None,
+    })
+}
+
+/// UploadPartCopy で分割してコピーする
+/// ※ マルチパートではメタデータが引き継がれないため、置き換えない場合はコピー元の値を設定する
+/// ※ すべてのパートで src のバージョンと head の ETag を指定し、異なる世代のパートが混ざらないようにする
+async fn multipart_copy(
+    client: &Client,
+    src: &ObjectRef,
+    dst: &ObjectRef,
+    head: &HeadObjectOutput,
+    size: u64,
+    options: &CopyOptions,
+) -> Result<PutResult, BoxError> {
+    let mut attributes = options.replace.clone().unwrap_or_else(|| PutOptions {
+        content_type: head.content_type.clone(),
+        metadata: head.metadata.clone().unwrap_or_default(),
+        cache_control: head.cache_control.clone(),
+        content_encoding: head.content_encoding.clone(),
+        storage_class: head.storage_class.clone(),
+        acl: None,
+        encryption: None,
+        checksum: None,
+        progress: None,
+        compression: None,
+    });
+    attributes.encryption = options.dest_encryption().cloned();
+    // UploadPartCopy ではパートのチェックサムを計算しないため、複合チェックサムは要求しない
+    attributes.checksum = None;
+    let dest = customer_key_headers(attributes.encryption.as_ref())?;
+    let source = customer_key_headers(options.source_encryption.as_ref())?;
+    let upload_id = create_upload(
+        client,
+        &dst.bucket,
+        &dst.key,
+        attributes.content_type.clone(),
+        &attributes,
+    )
+    .await?;
+
+    // パート数が上限（10,000）を超えないよう、アップロードと同じ方法でパートサイズを決める
+    let part_size = effective_part_size(size, options.part_size).min(COPY_OBJECT_LIMIT);
+    let tasks = (0..size)
+        .step_by(part_size as usize)
+        .enumerate()
+        .map(|(i, start)| {
+            let request = client
+                .upload_part_copy()
+                .copy_source(src.copy_source())
+                .set_copy_source_if_match(head.e_tag.clone())
+                .copy_source_range(format!(
+                    "bytes={}-{}",
+                    start,
+                    (start + part_size).min(size) - 1
+                ))
+                .bucket(&dst.bucket)
+                .key(&dst.key)
+                .upload_id(&upload_id)
+                .part_number(i as i32 + 1)
+                .set_sse_customer_algorithm(dest.algorithm.clone())
+                .set_sse_customer_key(dest.key.clone())
+                .set_sse_customer_key_md5(dest.key_md5.clone())
+                .set_copy_source_sse_customer_algorithm(source.algorithm.clone())
+                .set_copy_source_sse_customer_key(source.key.clone())
+                .set_copy_source_sse_customer_key_md5(source.key_md5.clone());
+            let length = (start + part_size).min(size) - start;
+            let progress = options.progress.clone();
+            async move {
+                let resp = request.send().await?;
+                if let Some(progress) = &progress {
+                    progress.transferred(length);
+                }
+                let e_tag = resp
+                    .copy_part_result
+                    .and_then(|r| r.e_tag)
+                    .ok_or("パートの ETag が返されていません")?;
+                Ok(PartState {
+                    part_number: i as i32 + 1,
+                    e_tag,
+                    checksum: None,
+                })
+            }
+        });
+
+    let mut parts = Vec::new();
+    let result = run_concurrently(tasks, options.concurrency, |part| {
+        parts.push(part);
+        Ok(())
+    })
+    .await;
+    let result = match result {
+        Ok(()) => {
+            complete_upload(
+                client,
+                &dst.bucket,
+                &dst.key,
+                &upload_id,
+                parts,
+                &attributes,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = client
+            .abort_multipart_upload()
+            .bucket(&dst.bucket)
+            .key(&dst.key)
+            .upload_id(&upload_id)
+            .send()
+            .await;
+    }
+    result
+}
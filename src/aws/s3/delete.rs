@@ -0,0 +1,319 @@
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use aws_sdk_s3::Client;
+use dotenv::dotenv;
+
+use super::error::S3Error;
+use super::list::list_stream_matching;
+use super::pattern::KeyPattern;
+use super::{run_sync, BoxError};
+
+/// DeleteObjects 1 回で削除できるキーの上限
+const MAX_DELETE_KEYS: usize = 1000;
+
+/// 削除対象のオブジェクト（バージョン ID を省略すると最新版が対象）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectId {
+    pub key: String,
+    pub version_id: Option<String>,
+}
+
+impl From<&str> for ObjectId {
+    fn from(key: &str) -> Self {
+        ObjectId {
+            key: key.to_string(),
+            version_id: None,
+        }
+    }
+}
+
+/// 削除に失敗したオブジェクト
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteFailure {
+    pub key: String,
+    pub version_id: Option<String>,
+    /// S3 のエラーコード（AccessDenied など）
+    pub code: Option<String>,
+    pub message: Option<String>,
+}
+
+/// 一括削除の結果
+/// ※ dry_run の場合、deleted には削除予定のオブジェクトが入る
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeleteReport {
+    pub deleted: Vec<ObjectId>,
+    pub errors: Vec<DeleteFailure>,
+}
+
+impl DeleteReport {
+    fn merge(&mut self, other: DeleteReport) {
+        self.deleted.extend(other.deleted);
+        self.errors.extend(other.errors);
+    }
+}
+
+/// 保護対象のバケットで削除する場合の確認
+/// ※ 保護対象のバケットでは、confirm に同じバケット名を指定しないと削除できない
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeleteGuard {
+    /// 確認として指定するバケット名
+    pub confirm: Option<String>,
+    /// 保護対象のバケット（None の場合は PROTECTED_BUCKETS 環境変数のカンマ区切りの一覧）
+    pub protected: Option<Vec<String>>,
+}
+
+impl DeleteGuard {
+    /// 確認としてバケット名を指定する
+    pub fn confirm(bucket: &str) -> Self {
+        DeleteGuard {
+            confirm: Some(bucket.to_string()),
+            protected: None,
+        }
+    }
+
+    /// バケットが保護対象かどうか
+    pub fn is_protected(&self, bucket: &str) -> bool {
+        match &self.protected {
+            Some(protected) => protected.iter().any(|b| b == bucket),
+            None => is_protected(bucket),
+        }
+    }
+
+    /// 保護対象のバケットの場合、確認としてバケット名が指定されているか検証する
+    pub(super) fn check(&self, bucket: &str) -> Result<(), BoxError> {
+        if self.is_protected(bucket) && self.confirm.as_deref() != Some(bucket) {
+            return Err(S3Error::ConfirmationRequired {
+                bucket: bucket.to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// プレフィックス削除のオプション
+#[derive(Debug, Clone, Default)]
+pub struct DeletePrefixOptions {
+    /// 全バージョンと削除マーカーも削除する（バージョニング有効なバケット向け）
+    pub all_versions: bool,
+    /// 実行せずに削除予定のオブジェクトを返すだけにする
+    pub dry_run: bool,
+    /// 保護対象のバケットを削除する場合の確認
+    pub guard: DeleteGuard,
+}
+
+/// オブジェクトを 1 つ削除する（非同期版）
+/// ※ 保護対象のバケットは guard.confirm にバケット名が必要
+pub async fn delete_object_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    guard: &DeleteGuard,
+) -> Result<(), BoxError> {
+    guard.check(bucket)?;
+    client
+        .delete_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// 複数のオブジェクトを削除する（非同期版）
+/// ※ 1000 件ずつ DeleteObjects を呼び出し、キーごとの失敗は DeleteReport に入れて返す
+/// ※ 保護対象のバケットは guard.confirm にバケット名が必要
+pub async fn delete_objects_async(
+    client: &Client,
+    bucket: &str,
+    ids: &[ObjectId],
+    guard: &DeleteGuard,
+) -> Result<DeleteReport, BoxError> {
+    guard.check(bucket)?;
+    let mut report = DeleteReport::default();
+    for chunk in ids.chunks(MAX_DELETE_KEYS) {
+        report.merge(delete_batch(client, bucket, chunk).await?);
+    }
+    Ok(report)
+}
+
+/// プレフィックス配下のオブジェクトをすべて削除する（非同期版）
+/// ※ 一覧を取得しながら 1000 件ずつ削除するため、件数が多くてもメモリを使い切らない
+pub async fn delete_prefix_async(
+    client: &Client,
+    bucket: &str,
+    prefix: &str,
+    options: &DeletePrefixOptions,
+) -> Result<DeleteReport, BoxError> {
+    delete_matching_async(client, bucket, &KeyPattern::under(prefix), options).await
+}
+
+/// パターンに一致するオブジェクトをすべて削除する（非同期版）
+/// ※ all_versions の場合は、キーが一致する全バージョンと削除マーカーを削除する
+pub async fn delete_matching_async(
+    client: &Client,
+    bucket: &str,
+    pattern: &KeyPattern,
+    options: &DeletePrefixOptions,
+) -> Result<DeleteReport, BoxError> {
+    if !options.dry_run {
+        options.guard.check(bucket)?;
+    }
+
+    let mut report = DeleteReport::default();
+    let mut batch = Vec::with_capacity(MAX_DELETE_KEYS);
+    let flush = |batch: Vec<ObjectId>| async {
+        if options.dry_run {
+            Ok(DeleteReport {
+                deleted: batch,
+                errors: Vec::new(),
+            })
+        } else {
+            delete_batch(client, bucket, &batch).await
+        }
+    };
+
+    if options.all_versions {
+        let mut key_marker = None;
+        let mut version_id_marker = None;
+        loop {
+            let page = client
+                .list_object_versions()
+                .bucket(bucket)
+                .prefix(pattern.prefix())
+                .set_key_marker(key_marker.take())
+                .set_version_id_marker(version_id_marker.take())
+                .send()
+                .await?;
+            let versions = page
+                .versions
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| (v.key, v.version_id));
+            let markers = page
+                .delete_markers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|m| (m.key, m.version_id));
+            for (key, version_id) in versions.chain(markers) {
+                let Some(key) = key.filter(|key| pattern.is_match(key)) else {
+                    continue;
+                };
+                batch.push(ObjectId { key, version_id });
+                if batch.len() == MAX_DELETE_KEYS {
+                    report.merge(flush(std::mem::take(&mut batch)).await?);
+                }
+            }
+            if !page.is_truncated.unwrap_or(false) {
+                break;
+            }
+            key_marker = page.next_key_marker;
+            version_id_marker = page.next_version_id_marker;
+        }
+    } else {
+        let mut stream = list_stream_matching(client, bucket, pattern);
+        while let Some(object) = stream.next_object().await {
+            batch.push(ObjectId::from(object?.key.as_str()));
+            if batch.len() == MAX_DELETE_KEYS {
+                report.merge(flush(std::mem::take(&mut batch)).await?);
+            }
+        }
+    }
+    if !batch.is_empty() {
+        report.merge(flush(batch).await?);
+    }
+    Ok(report)
+}
+
+/// オブジェクトを 1 つ削除する
+pub fn delete_object(
+    bucket: &str,
+    key: &str,
+    guard: &DeleteGuard,
+) -> Result<(), Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { delete_object_async(&s3, bucket, key, guard).await })
+}
+
+/// 複数のオブジェクトを削除する
+pub fn delete_objects(
+    bucket: &str,
+    ids: &[ObjectId],
+    guard: &DeleteGuard,
+) -> Result<DeleteReport, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { delete_objects_async(&s3, bucket, ids, guard).await })
+}
+
+/// プレフィックス配下のオブジェクトをすべて削除する
+pub fn delete_prefix(
+    bucket: &str,
+    prefix: &str,
+    options: &DeletePrefixOptions,
+) -> Result<DeleteReport, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { delete_prefix_async(&s3, bucket, prefix, options).await })
+}
+
+/// パターンに一致するオブジェクトをすべて削除する
+pub fn delete_matching(
+    bucket: &str,
+    pattern: &KeyPattern,
+    options: &DeletePrefixOptions,
+) -> Result<DeleteReport, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { delete_matching_async(&s3, bucket, pattern, options).await })
+}
+
+/// バケットが PROTECTED_BUCKETS 環境変数に含まれているかどうか
+pub fn is_protected(bucket: &str) -> bool {
+    dotenv().ok();
+    std::env::var("PROTECTED_BUCKETS")
+        .map(|list| list.split(',').any(|b| b.trim() == bucket))
+        .unwrap_or(false)
+}
+
+/// 1000 件以下のオブジェクトを DeleteObjects で削除する
+async fn delete_batch(
+    client: &Client,
+    bucket: &str,
+    ids: &[ObjectId],
+) -> Result<DeleteReport, BoxError> {
+    let objects = ids
+        .iter()
+        .map(|id| {
+            ObjectIdentifier::builder()
+                .key(&id.key)
+                .set_version_id(id.version_id.clone())
+                .build()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let delete = Delete::builder()
+        .set_objects(Some(objects))
+        .quiet(false)
+        .build()?;
+    let resp = client
+        .delete_objects()
+        .bucket(bucket)
+        .delete(delete)
+        .send()
+        .await?;
+
+    Ok(DeleteReport {
+        deleted: resp
+            .deleted
+            .unwrap_or_default()
+            .into_iter()
+            .map(|d| ObjectId {
+                key: d.key.unwrap_or_default(),
+                version_id: d.version_id,
+            })
+            .collect(),
+        errors: resp
+            .errors
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| DeleteFailure {
+                key: e.key.unwrap_or_default(),
+                version_id: e.version_id,
+                code: e.code,
+                message: e.message,
+            })
+            .collect(),
+    })
+}
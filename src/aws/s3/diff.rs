@@ -0,0 +1,578 @@
+use aws_sdk_s3::types::ChecksumMode;
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::bucket::client_for_bucket;
+use super::checksum::{composite_parts, expected_checksum, file_checksum, ChecksumAlgorithm};
+use super::download::{is_etag_opaque, is_md5_etag};
+use super::encryption::{customer_key_headers, CustomerKeyHeaders, Encryption};
+use super::list::ObjectInfo;
+use super::ranged::file_etag;
+use super::sync::{list_remote, walk_local, Filter, LocalFile};
+use super::uri::S3Uri;
+use super::{run_concurrently, run_sync, BoxError};
+
+/// 差分比較のオプション
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// 対象にするパターン（空なら全て）。相対パスに対するグロブで、* は / を越えない
+    pub include: Vec<String>,
+    /// 対象から外すパターン。include より優先する
+    pub exclude: Vec<String>,
+    /// サイズが同じものについて、ETag・チェックサムで内容も比較する
+    /// ※ false の場合はサイズだけで比較し、サイズが同じものは一致として数える
+    pub compare_content: bool,
+    /// 同時に実行する内容の比較の数
+    pub concurrency: usize,
+    /// SSE-C で暗号化されたオブジェクトの鍵（両側で共通）
+    pub encryption: Option<Encryption>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            compare_content: true,
+            concurrency: 8,
+            encryption: None,
+        }
+    }
+}
+
+/// 比較の左側・右側
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffSide {
+    Left,
+    Right,
+}
+
+impl fmt::Display for DiffSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffSide::Left => write!(f, "left"),
+            DiffSide::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// 差分の種類
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffKind {
+    /// 左側にだけある
+    OnlyLeft,
+    /// 右側にだけある
+    OnlyRight,
+    /// サイズが違う
+    SizeMismatch { left: u64, right: u64 },
+    /// サイズは同じだが内容が違う
+    /// ※ method は比較に使った値（ETag / MD5 / CRC32C / SHA256）
+    ContentMismatch {
+        method: String,
+        left: String,
+        right: String,
+    },
+}
+
+/// 1 つのキーの差分
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffEntry {
+    /// プレフィックス（ディレクトリ）からの相対キー
+    pub key: String,
+    pub kind: DiffKind,
+    /// 両側にある場合、更新日時が新しい側（秒単位で同じか、日時が不明なら None）
+    pub newer: Option<DiffSide>,
+}
+
+impl fmt::Display for DiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            DiffKind::OnlyLeft => write!(f, "< {}", self.key)?,
+            DiffKind::OnlyRight => write!(f, "> {}", self.key)?,
+            DiffKind::SizeMismatch { left, right } => {
+                write!(f, "~ {}: size {} != {}", self.key, left, right)?
+            }
+            DiffKind::ContentMismatch {
+                method,
+                left,
+                right,
+            } => write!(f, "~ {}: {} {} != {}", self.key, method, left, right)?,
+        }
+        if let Some(newer) = self.newer {
+            write!(f, " ({} is newer)", newer)?;
+        }
+        Ok(())
+    }
+}
+
+/// 2 つのプレフィックス（またはローカルディレクトリ）の差分
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixDiff {
+    /// 左側の S3 URI またはローカルのパス
+    pub left: String,
+    /// 右側の S3 URI またはローカルのパス
+    pub right: String,
+    /// 差分（キーの昇順）
+    pub entries: Vec<DiffEntry>,
+    /// 一致したキーの数
+    pub identical: u64,
+    /// サイズは同じだが、ETag・チェックサムから内容を比較できなかったキー（昇順）
+    /// ※ パートサイズの違うマルチパートの ETag 同士や、SSE-KMS の ETag など
+    pub unverified: Vec<String>,
+}
+
+impl PrefixDiff {
+    /// 差分が無いかどうか（unverified は差分として扱わない）
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 種類ごとの件数を 1 行にまとめる
+    pub fn summary(&self) -> String {
+        let count = |f: fn(&DiffKind) -> bool| self.entries.iter().filter(|e| f(&e.kind)).count();
+        format!(
+            "{} <> {}: 左のみ {}, 右のみ {}, サイズ違い {}, 内容違い {}, 一致 {}, 未確認 {}",
+            self.left,
+            self.right,
+            count(|k| matches!(k, DiffKind::OnlyLeft)),
+            count(|k| matches!(k, DiffKind::OnlyRight)),
+            count(|k| matches!(k, DiffKind::SizeMismatch { .. })),
+            count(|k| matches!(k, DiffKind::ContentMismatch { .. })),
+            self.identical,
+            self.unverified.len()
+        )
+    }
+}
+
+/// 1 差分 1 行（"<" 左のみ、">" 右のみ、"~" 不一致、"?" 未確認）と、最後に件数のまとめを表示する
+impl fmt::Display for PrefixDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        for key in &self.unverified {
+            writeln!(f, "? {}", key)?;
+        }
+        writeln!(f, "{}", self.summary())
+    }
+}
+
+/// 2 つの場所を比較する（非同期版）
+/// ※ left / right は s3://bucket/prefix/ 形式の URI か、ローカルディレクトリのパス
+/// ※ URI のキー部分はディレクトリとして扱い、そこからの相対キーで突き合わせる
+pub async fn diff_prefixes_async(
+    client: &Client,
+    left: &str,
+    right: &str,
+    options: &DiffOptions,
+) -> Result<PrefixDiff, BoxError> {
+    let filter = Filter::new(&options.include, &options.exclude)?;
+    let lefts = list_location(client, left, &filter).await?;
+    let rights = list_location(client, right, &filter).await?;
+
+    let mut diff = PrefixDiff {
+        left: left.to_string(),
+        right: right.to_string(),
+        entries: Vec::new(),
+        identical: 0,
+        unverified: Vec::new(),
+    };
+    let mut same_size = Vec::new();
+    let keys: BTreeSet<&String> = lefts.keys().chain(rights.keys()).collect();
+    for key in keys {
+        let (kind, newer) = match (lefts.get(key), rights.get(key)) {
+            (Some(_), None) => (DiffKind::OnlyLeft, None),
+            (None, Some(_)) => (DiffKind::OnlyRight, None),
+            (Some(l), Some(r)) if l.size() != r.size() => (
+                DiffKind::SizeMismatch {
+                    left: l.size(),
+                    right: r.size(),
+                },
+                newer_side(l, r),
+            ),
+            (Some(l), Some(r)) => {
+                if options.compare_content {
+                    same_size.push((key.clone(), l.clone(), r.clone()));
+                } else {
+                    diff.identical += 1;
+                }
+                continue;
+            }
+            (None, None) => continue,
+        };
+        diff.entries.push(DiffEntry {
+            key: key.clone(),
+            kind,
+            newer,
+        });
+    }
+
+    let customer = customer_key_headers(options.encryption.as_ref())?;
+    let tasks = same_size.into_iter().map(|(key, l, r)| {
+        let customer = customer.clone();
+        async move {
+            let content = compare_content(&l, &r, &customer).await?;
+            Ok((key, content, newer_side(&l, &r)))
+        }
+    });
+    run_concurrently(tasks, options.concurrency, |(key, content, newer)| {
+        match content {
+            Content::Same => diff.identical += 1,
+            Content::Unknown => diff.unverified.push(key),
+            Content::Differ {
+                method,
+                left,
+                right,
+            } => diff.entries.push(DiffEntry {
+                key,
+                kind: DiffKind::ContentMismatch {
+                    method,
+                    left,
+                    right,
+                },
+                newer,
+            }),
+        }
+        Ok(())
+    })
+    .await?;
+
+    diff.entries.sort_by(|a, b| a.key.cmp(&b.key));
+    diff.unverified.sort();
+    Ok(diff)
+}
+
+/// 2 つの場所を比較する
+pub fn diff_prefixes(
+    left: &str,
+    right: &str,
+    options: &DiffOptions,
+) -> Result<PrefixDiff, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { diff_prefixes_async(&s3, left, right, options).await })
+}
+
+/// 比較対象の 1 つ（オブジェクトまたはローカルファイル）
+#[derive(Debug, Clone)]
+enum Item {
+    Object {
+        client: Client,
+        bucket: String,
+        object: ObjectInfo,
+    },
+    File(LocalFile),
+}
+
+impl Item {
+    fn size(&self) -> u64 {
+        match self {
+            Item::Object { object, .. } => object.size,
+            Item::File(file) => file.size,
+        }
+    }
+
+    fn modified(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Item::Object { object, .. } => object.last_modified,
+            Item::File(file) => Some(file.modified),
+        }
+    }
+}
+
+/// 場所の配下を「相対キー → 比較対象」で返す
+/// ※ ローカルディレクトリが存在しない場合はエラーにする（S3 のプレフィックスは空として扱う）
+async fn list_location(
+    client: &Client,
+    location: &str,
+    filter: &Filter,
+) -> Result<BTreeMap<String, Item>, BoxError> {
+    if location.starts_with("s3://") {
+        let uri = S3Uri::from_str(location)?.to_dir();
+        let client = client_for_bucket(client, uri.bucket()).await?;
+        let objects = list_remote(&client, uri.bucket(), uri.key(), filter).await?;
+        return Ok(objects
+            .into_iter()
+            .map(|(rel, object)| {
+                let item = Item::Object {
+                    client: client.clone(),
+                    bucket: uri.bucket().to_string(),
+                    object,
+                };
+                (rel, item)
+            })
+            .collect());
+    }
+
+    let root = Path::new(location);
+    if !root.is_dir() {
+        return Err(format!("ディレクトリが存在しません: {}", location).into());
+    }
+    Ok(walk_local(root, filter)?
+        .into_iter()
+        .map(|(rel, file)| (rel, Item::File(file)))
+        .collect())
+}
+
+/// 更新日時が新しい側（S3 の更新日時は秒単位のため、秒で比較する）
+fn newer_side(left: &Item, right: &Item) -> Option<DiffSide> {
+    let (l, r) = (left.modified()?.timestamp(), right.modified()?.timestamp());
+    match l.cmp(&r) {
+        std::cmp::Ordering::Greater => Some(DiffSide::Left),
+        std::cmp::Ordering::Less => Some(DiffSide::Right),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+/// 内容の比較結果
+enum Content {
+    Same,
+    Differ {
+        method: String,
+        left: String,
+        right: String,
+    },
+    /// 比較できる値が無い
+    Unknown,
+}
+
+impl Content {
+    fn compared(method: &str, left: String, right: String) -> Self {
+        if left == right {
+            Content::Same
+        } else {
+            Content::Differ {
+                method: method.to_string(),
+                left,
+                right,
+            }
+        }
+    }
+
+    /// 左右を入れ替える
+    fn swap(self) -> Self {
+        match self {
+            Content::Differ {
+                method,
+                left,
+                right,
+            } => Content::Differ {
+                method,
+                left: right,
+                right: left,
+            },
+            other => other,
+        }
+    }
+}
+
+/// サイズが同じ 2 つの内容を比較する
+async fn compare_content(
+    left: &Item,
+    right: &Item,
+    customer: &CustomerKeyHeaders,
+) -> Result<Content, BoxError> {
+    match (left, right) {
+        (Item::File(l), Item::File(r)) => Ok(Content::compared(
+            "MD5",
+            file_etag(&l.path, None)?,
+            file_etag(&r.path, None)?,
+        )),
+        (
+            Item::Object {
+                client: l_client,
+                bucket: l_bucket,
+                object: l_object,
+            },
+            Item::Object {
+                client: r_client,
+                bucket: r_bucket,
+                object: r_object,
+            },
+        ) => {
+            compare_objects(
+                (l_client, l_bucket, l_object),
+                (r_client, r_bucket, r_object),
+                customer,
+            )
+            .await
+        }
+        (
+            Item::Object {
+                client,
+                bucket,
+                object,
+            },
+            Item::File(file),
+        ) => compare_object_file(client, bucket, object, file, customer).await,
+        (
+            Item::File(file),
+            Item::Object {
+                client,
+                bucket,
+                object,
+            },
+        ) => Ok(compare_object_file(client, bucket, object, file, customer)
+            .await?
+            .swap()),
+    }
+}
+
+/// オブジェクト同士を比較する
+/// ※ ETag が同じなら一致。違う場合はチェックサム、単純な MD5 の ETag の順に比べる
+async fn compare_objects(
+    (l_client, l_bucket, l_object): (&Client, &str, &ObjectInfo),
+    (r_client, r_bucket, r_object): (&Client, &str, &ObjectInfo),
+    customer: &CustomerKeyHeaders,
+) -> Result<Content, BoxError> {
+    if l_object.e_tag.is_some() && l_object.e_tag == r_object.e_tag {
+        return Ok(Content::Same);
+    }
+
+    let l_facts = remote_facts(l_client, l_bucket, &l_object.key, customer).await?;
+    let r_facts = remote_facts(r_client, r_bucket, &r_object.key, customer).await?;
+    for algorithm in [ChecksumAlgorithm::Crc32c, ChecksumAlgorithm::Sha256] {
+        let (Some(l), Some(r)) = (l_facts.checksum(algorithm), r_facts.checksum(algorithm)) else {
+            continue;
+        };
+        // 複合チェックサムはパートサイズが違うと値も変わるため、一致した場合だけ使う
+        if l == r || (composite_parts(l).is_none() && composite_parts(r).is_none()) {
+            return Ok(Content::compared(
+                &algorithm.to_string(),
+                l.to_string(),
+                r.to_string(),
+            ));
+        }
+    }
+
+    if let (Some(l), Some(r)) = (&l_object.e_tag, &r_object.e_tag) {
+        if !l_facts.opaque && !r_facts.opaque && is_md5_etag(l) && is_md5_etag(r) {
+            return Ok(Content::compared("ETag", l.clone(), r.clone()));
+        }
+    }
+    Ok(Content::Unknown)
+}
+
+/// オブジェクトとローカルファイルを比較する（値はオブジェクト, ファイルの順で返す）
+/// ※ オブジェクトにチェックサムがあればそれを、無ければ ETag（MD5）をファイルから計算して比べる
+async fn compare_object_file(
+    client: &Client,
+    bucket: &str,
+    object: &ObjectInfo,
+    file: &LocalFile,
+    customer: &CustomerKeyHeaders,
+) -> Result<Content, BoxError> {
+    let facts = remote_facts(client, bucket, &object.key, customer).await?;
+    for algorithm in [ChecksumAlgorithm::Crc32c, ChecksumAlgorithm::Sha256] {
+        let value = facts.checksum(algorithm);
+        let Some(expected) = expected_checksum(
+            client,
+            bucket,
+            &object.key,
+            None,
+            customer,
+            algorithm,
+            value,
+        )
+        .await?
+        else {
+            continue;
+        };
+        let actual = file_checksum(&file.path, algorithm, expected.part_size)?;
+        // 複合チェックサムは 1 パート目の大きさから推定した境界で計算するため、
+        // 一致しない場合はパートサイズが不揃いな可能性があり、内容の違いとは断定できない
+        if composite_parts(&expected.value).is_none() || expected.value == actual {
+            return Ok(Content::compared(
+                &algorithm.to_string(),
+                expected.value,
+                actual,
+            ));
+        }
+    }
+
+    let Some(e_tag) = object.e_tag.as_deref().filter(|_| !facts.opaque) else {
+        return Ok(Content::Unknown);
+    };
+    if is_md5_etag(e_tag) {
+        return Ok(Content::compared(
+            "ETag",
+            e_tag.to_string(),
+            file_etag(&file.path, None)?,
+        ));
+    }
+    if composite_parts(e_tag).is_none() {
+        return Ok(Content::Unknown);
+    }
+    let part_size = first_part_size(client, bucket, &object.key, customer).await?;
+    if part_size == 0 {
+        return Ok(Content::Unknown);
+    }
+    // マルチパートの ETag も同様に、一致しなければ内容の違いとは断定できない
+    let actual = file_etag(&file.path, Some(part_size))?;
+    if actual != e_tag {
+        return Ok(Content::Unknown);
+    }
+    Ok(Content::Same)
+}
+
+/// HeadObject で分かる、内容の比較に使える情報
+struct RemoteFacts {
+    /// ETag が内容の MD5 にならない（SSE-KMS・SSE-C）
+    opaque: bool,
+    crc32c: Option<String>,
+    sha256: Option<String>,
+}
+
+impl RemoteFacts {
+    fn checksum(&self, algorithm: ChecksumAlgorithm) -> Option<&str> {
+        algorithm.pick(self.crc32c.as_deref(), self.sha256.as_deref())
+    }
+}
+
+async fn remote_facts(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    customer: &CustomerKeyHeaders,
+) -> Result<RemoteFacts, BoxError> {
+    let head = client
+        .head_object()
+        .bucket(bucket)
+        .key(key)
+        .checksum_mode(ChecksumMode::Enabled)
+        .set_sse_customer_algorithm(customer.algorithm.clone())
+        .set_sse_customer_key(customer.key.clone())
+        .set_sse_customer_key_md5(customer.key_md5.clone())
+        .send()
+        .await?;
+    Ok(RemoteFacts {
+        opaque: is_etag_opaque(
+            head.server_side_encryption.as_ref(),
+            head.sse_customer_algorithm.as_deref(),
+        ),
+        crc32c: head.checksum_crc32_c,
+        sha256: head.checksum_sha256,
+    })
+}
+
+/// マルチパートでアップロードされたオブジェクトの 1 パート目の大きさ
+async fn first_part_size(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    customer: &CustomerKeyHeaders,
+) -> Result<u64, BoxError> {
+    let head = client
+        .head_object()
+        .bucket(bucket)
+        .key(key)
+        .part_number(1)
+        .set_sse_customer_algorithm(customer.algorithm.clone())
+        .set_sse_customer_key(customer.key.clone())
+        .set_sse_customer_key_md5(customer.key_md5.clone())
+        .send()
+        .await?;
+    Ok(head.content_length.unwrap_or(0).max(0) as u64)
+}
@@ -0,0 +1,382 @@
+use aws_sdk_s3::operation::get_object::GetObjectOutput;
+use aws_sdk_s3::types::{ChecksumMode, ServerSideEncryption};
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use md5::{Digest, Md5};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::bandwidth::throttle;
+use super::bucket::client_for_bucket;
+use super::checksum::{expected_checksum, ChecksumAlgorithm, ExpectedChecksum};
+use super::compression::{Compression, DecodingWriter};
+use super::encryption::{customer_key_headers, Encryption, EncryptionInfo};
+use super::error::S3Error;
+use super::list::list_stream_matching;
+use super::pattern::KeyPattern;
+use super::progress::ProgressReporter;
+use super::{run_concurrently, run_sync, to_chrono, trim_etag, BoxError};
+
+/// まとめてダウンロードする際に同時に実行するダウンロードの数
+const DOWNLOAD_CONCURRENCY: usize = 8;
+
+/// ダウンロード結果を保持する構造体
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadInfo {
+    /// 受信したバイト数
+    pub size: u64,
+    /// ETag（前後のダブルクォートは取り除いた値）
+    pub e_tag: Option<String>,
+    /// 最終更新日時
+    pub last_modified: Option<DateTime<Utc>>,
+    /// オブジェクトに適用されている暗号化
+    pub encryption: Option<EncryptionInfo>,
+    /// 照合したチェックサム（GetOptions.checksum を指定し、オブジェクトに記録されていた場合のみ）
+    pub checksum: Option<String>,
+    /// 展開した圧縮形式（GetOptions.decompress を指定し、圧縮されていた場合のみ）
+    pub compression: Option<Compression>,
+}
+
+/// ダウンロード時に指定できるオプション
+#[derive(Debug, Clone, Default)]
+pub struct GetOptions {
+    /// オブジェクトが SSE-C で暗号化されている場合の鍵
+    /// ※ SSE-S3 / SSE-KMS は S3 側で復号されるため指定不要（指定しても無視する）
+    pub encryption: Option<Encryption>,
+    /// 受信したデータと照合するチェックサム
+    /// ※ アップロード時に同じアルゴリズムのチェックサムが記録されていないオブジェクトは検証しない
+    /// ※ 複合チェックサムが一致しない場合は、パートサイズが不揃いな可能性があるため S3Error::ChecksumUnverifiable になる
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// 進捗の通知先
+    pub progress: Option<ProgressReporter>,
+    /// 圧縮されたオブジェクトを展開して書き込む
+    /// ※ Content-Encoding（gzip / zstd）、次いでキーの拡張子（.gz / .zst）から圧縮形式を判定する
+    /// ※ サイズ・ETag・チェックサムは展開前のデータで検証する
+    /// ※ S3ObjectReader では無視する
+    pub decompress: bool,
+}
+
+/// オブジェクトを取得して writer に書き込む（非同期版）
+/// ※ 受信サイズと、ETag が MD5 の場合はその値も検証する
+pub async fn get_object_to_writer_async<W: Write>(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    writer: &mut W,
+    options: &GetOptions,
+) -> Result<DownloadInfo, BoxError> {
+    download_to_writer(client, bucket, key, None, writer, options).await
+}
+
+/// オブジェクトの内容をバイト列として取得する（非同期版）
+pub async fn get_object_bytes_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    options: &GetOptions,
+) -> Result<Vec<u8>, BoxError> {
+    let mut buf = Vec::new();
+    get_object_to_writer_async(client, bucket, key, &mut buf, options).await?;
+    Ok(buf)
+}
+
+/// オブジェクトをファイルに保存する（非同期版）
+/// ※ 一時ファイルに書き込んでからリネームするため、途中で失敗しても既存ファイルは壊れない
+/// ※ ファイルの更新日時はオブジェクトの Last-Modified に合わせる
+pub async fn get_object_to_file_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &GetOptions,
+) -> Result<DownloadInfo, BoxError> {
+    download_to_file(client, bucket, key, None, path, options).await
+}
+
+/// パターンに一致するオブジェクトをまとめてローカルディレクトリにダウンロードする（非同期版）
+/// ※ 保存先は local_dir に、パターンの起点（KeyPattern::base）からの相対キーを付けたパス
+/// ※ "/" で終わるディレクトリ用のオブジェクトや、".." を含むキーは対象にしない
+/// ※ 戻り値は保存したファイルのパスの一覧（昇順）
+pub async fn download_matching_async(
+    client: &Client,
+    bucket: &str,
+    pattern: &KeyPattern,
+    local_dir: &Path,
+    options: &GetOptions,
+) -> Result<Vec<PathBuf>, BoxError> {
+    let client = client_for_bucket(client, bucket).await?;
+    let mut targets = Vec::new();
+    let mut stream = list_stream_matching(&client, bucket, pattern);
+    while let Some(object) = stream.next_object().await {
+        let key = object?.key;
+        let rel = key.strip_prefix(pattern.base()).unwrap_or(&key);
+        if rel.is_empty() || rel.ends_with('/') || rel.split('/').any(|c| c == "..") {
+            continue;
+        }
+        let path = rel
+            .split('/')
+            .fold(local_dir.to_path_buf(), |p, c| p.join(c));
+        targets.push((key, path));
+    }
+
+    let tasks = targets.into_iter().map(|(key, path)| {
+        let (client, bucket, options) = (client.clone(), bucket.to_string(), options.clone());
+        async move {
+            download_to_file(&client, &bucket, &key, None, &path, &options).await?;
+            Ok(path)
+        }
+    });
+    let mut saved = Vec::new();
+    run_concurrently(tasks, DOWNLOAD_CONCURRENCY, |path| {
+        saved.push(path);
+        Ok(())
+    })
+    .await?;
+    saved.sort();
+    Ok(saved)
+}
+
+/// オブジェクトを取得して writer に書き込む
+pub fn get_object_to_writer<W: Write>(
+    bucket: &str,
+    key: &str,
+    writer: &mut W,
+    options: &GetOptions,
+) -> Result<DownloadInfo, Box<dyn std::error::Error>> {
+    run_sync(
+        |s3| async move { get_object_to_writer_async(&s3, bucket, key, writer, options).await },
+    )
+}
+
+/// オブジェクトの内容をバイト列として取得する
+pub fn get_object_bytes(
+    bucket: &str,
+    key: &str,
+    options: &GetOptions,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { get_object_bytes_async(&s3, bucket, key, options).await })
+}
+
+/// オブジェクトをファイルに保存する
+pub fn get_object_to_file(
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &GetOptions,
+) -> Result<DownloadInfo, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { get_object_to_file_async(&s3, bucket, key, path, options).await })
+}
+
+/// パターンに一致するオブジェクトをまとめてローカルディレクトリにダウンロードする
+pub fn download_matching(
+    bucket: &str,
+    pattern: &KeyPattern,
+    local_dir: &Path,
+    options: &GetOptions,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    run_sync(
+        |s3| async move { download_matching_async(&s3, bucket, pattern, local_dir, options).await },
+    )
+}
+
+/// バージョンを指定してオブジェクトを取得し、writer に書き込む
+/// ※ version_id が None の場合は最新バージョンを取得する
+pub(super) async fn download_to_writer<W: Write>(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    version_id: Option<&str>,
+    writer: &mut W,
+    options: &GetOptions,
+) -> Result<DownloadInfo, BoxError> {
+    let customer = customer_key_headers(options.encryption.as_ref())?;
+    let resp = client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .set_version_id(version_id.map(str::to_string))
+        .set_sse_customer_algorithm(customer.algorithm.clone())
+        .set_sse_customer_key(customer.key.clone())
+        .set_sse_customer_key_md5(customer.key_md5.clone())
+        .set_checksum_mode(options.checksum.map(|_| ChecksumMode::Enabled))
+        .send()
+        .await?;
+    let checksum = match options.checksum {
+        Some(algorithm) => {
+            let value = algorithm.pick(
+                resp.checksum_crc32_c.as_deref(),
+                resp.checksum_sha256.as_deref(),
+            );
+            expected_checksum(client, bucket, key, version_id, &customer, algorithm, value).await?
+        }
+        None => None,
+    };
+    let compression = if options.decompress {
+        Compression::detect(resp.content_encoding.as_deref(), key)
+    } else {
+        None
+    };
+    let mut writer = DecodingWriter::new(compression, writer)?;
+    let mut info = copy_body(key, resp, &mut writer, checksum, options.progress.as_ref()).await?;
+    writer.finish()?.flush()?;
+    info.compression = compression;
+    Ok(info)
+}
+
+/// バージョンを指定してオブジェクトをファイルに保存する
+pub(super) async fn download_to_file(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    version_id: Option<&str>,
+    path: &Path,
+    options: &GetOptions,
+) -> Result<DownloadInfo, BoxError> {
+    let tmp = temp_path(path)?;
+    let result: Result<DownloadInfo, BoxError> = async {
+        let mut file = File::create(&tmp)?;
+        let info = download_to_writer(client, bucket, key, version_id, &mut file, options).await?;
+        file.sync_all()?;
+        if let Some(last_modified) = info.last_modified {
+            file.set_modified(SystemTime::from(last_modified))?;
+        }
+        drop(file);
+        fs::rename(&tmp, path)?;
+        Ok(info)
+    }
+    .await;
+
+    // 失敗した場合は一時ファイルを残さない
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// レスポンスボディを逐次 writer に書き込み、サイズと ETag（指定があればチェックサムも）を検証する
+pub(super) async fn copy_body<W: Write>(
+    key: &str,
+    resp: GetObjectOutput,
+    writer: &mut W,
+    checksum: Option<ExpectedChecksum>,
+    progress: Option<&ProgressReporter>,
+) -> Result<DownloadInfo, BoxError> {
+    let expected_size = resp.content_length.map(|len| len.max(0) as u64);
+    let e_tag = resp.e_tag.as_deref().map(trim_etag);
+    let last_modified = resp.last_modified.as_ref().and_then(to_chrono);
+    let encryption = EncryptionInfo::from_headers(
+        resp.server_side_encryption.as_ref(),
+        resp.ssekms_key_id.as_deref(),
+        resp.sse_customer_algorithm.as_deref(),
+    );
+    let verify_md5 = e_tag.as_deref().is_some_and(is_md5_etag)
+        && !is_etag_opaque(
+            resp.server_side_encryption.as_ref(),
+            resp.sse_customer_algorithm.as_deref(),
+        );
+
+    if let Some(progress) = progress {
+        progress.file_started(key, expected_size.unwrap_or(0));
+    }
+
+    let mut hasher = Md5::new();
+    let mut calculator = checksum.as_ref().map(ExpectedChecksum::calculator);
+    let mut size = 0u64;
+    let mut body = resp.body;
+    while let Some(chunk) = body.try_next().await? {
+        writer.write_all(&chunk)?;
+        if verify_md5 {
+            hasher.update(&chunk);
+        }
+        if let Some(calculator) = &mut calculator {
+            calculator.update(&chunk);
+        }
+        size += chunk.len() as u64;
+        throttle(chunk.len() as u64).await;
+        if let Some(progress) = progress {
+            progress.transferred(chunk.len() as u64);
+        }
+    }
+    writer.flush()?;
+
+    if let Some(expected) = expected_size {
+        if expected != size {
+            return Err(S3Error::SizeMismatch {
+                key: key.to_string(),
+                expected,
+                actual: size,
+            }
+            .into());
+        }
+    }
+    if let (true, Some(expected)) = (verify_md5, e_tag.as_deref()) {
+        let actual = format!("{:x}", hasher.finalize());
+        if actual != expected {
+            return Err(S3Error::ETagMismatch {
+                key: key.to_string(),
+                expected: expected.to_string(),
+                actual,
+            }
+            .into());
+        }
+    }
+
+    if let (Some(expected), Some(calculator)) = (&checksum, calculator) {
+        expected.verify(key, &calculator.finish())?;
+    }
+    if let Some(progress) = progress {
+        progress.file_finished(key);
+    }
+
+    Ok(DownloadInfo {
+        size,
+        e_tag,
+        last_modified,
+        encryption,
+        checksum: checksum.map(|c| c.value),
+        compression: None,
+    })
+}
+
+/// ETag が単純な MD5（マルチパートの "-N" 付きではない）かどうか
+pub(super) fn is_md5_etag(e_tag: &str) -> bool {
+    e_tag.len() == 32 && e_tag.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// SSE-KMS や SSE-C で暗号化されている場合、ETag は内容の MD5 にならない
+pub(super) fn is_etag_opaque(
+    server_side_encryption: Option<&ServerSideEncryption>,
+    sse_customer_algorithm: Option<&str>,
+) -> bool {
+    let kms = matches!(
+        server_side_encryption,
+        Some(ServerSideEncryption::AwsKms) | Some(ServerSideEncryption::AwsKmsDsse)
+    );
+    kms || sse_customer_algorithm.is_some()
+}
+
+/// 保存先と同じディレクトリに置く一時ファイルのパスを返す
+/// ※ 保存先の親ディレクトリが無ければ作成する
+pub(super) fn temp_path(path: &Path) -> Result<PathBuf, BoxError> {
+    let file_name = path
+        .file_name()
+        .ok_or("保存先のファイル名が不正です")?
+        .to_string_lossy();
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    Ok(path.with_file_name(format!(".{}.part", file_name)))
+}
+
+/// temp_path で作る一時ファイル（.name.part）かどうか
+pub(super) fn is_temp_path(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| {
+            name.len() > ".part".len() + 1 && name.starts_with('.') && name.ends_with(".part")
+        })
+}
@@ -0,0 +1,136 @@
+use aws_sdk_s3::types::ServerSideEncryption;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use md5::{Digest, Md5};
+use std::collections::HashMap;
+use std::fmt;
+
+use super::BoxError;
+
+/// サーバー側暗号化の設定
+#[derive(Clone, PartialEq)]
+pub enum Encryption {
+    /// SSE-S3（S3 が管理する鍵で暗号化する）
+    S3,
+    /// SSE-KMS（key_id を省略すると aws/s3 マネージドキーを使う）
+    Kms {
+        /// KMS キーの ID、ARN、またはエイリアス
+        key_id: Option<String>,
+        /// 暗号化コンテキスト（空なら指定しない）
+        context: HashMap<String, String>,
+    },
+    /// SSE-C（利用者が用意した 32 バイトの AES-256 鍵で暗号化する）
+    /// ※ 取得やコピーの際にも同じ鍵を指定する必要がある
+    CustomerKey(Vec<u8>),
+}
+
+/// 鍵の値を出力しないよう、SSE-C は長さだけを表示する
+impl fmt::Debug for Encryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Encryption::S3 => write!(f, "S3"),
+            Encryption::Kms { key_id, context } => f
+                .debug_struct("Kms")
+                .field("key_id", key_id)
+                .field("context", context)
+                .finish(),
+            Encryption::CustomerKey(key) => write!(f, "CustomerKey(<{} bytes>)", key.len()),
+        }
+    }
+}
+
+/// 実際に適用されている暗号化（レスポンスヘッダーの値）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionInfo {
+    /// "AES256"（SSE-S3）、"aws:kms"（SSE-KMS）、"aws:kms:dsse"（DSSE-KMS）など
+    pub algorithm: Option<String>,
+    /// SSE-KMS で使われた KMS キーの ARN
+    pub kms_key_id: Option<String>,
+    /// SSE-C のアルゴリズム（"AES256"）
+    pub customer_algorithm: Option<String>,
+}
+
+impl EncryptionInfo {
+    /// レスポンスヘッダーの値から生成する（いずれも無い場合は None）
+    pub(super) fn from_headers(
+        algorithm: Option<&ServerSideEncryption>,
+        kms_key_id: Option<&str>,
+        customer_algorithm: Option<&str>,
+    ) -> Option<Self> {
+        if algorithm.is_none() && kms_key_id.is_none() && customer_algorithm.is_none() {
+            return None;
+        }
+        Some(EncryptionInfo {
+            algorithm: algorithm.map(|a| a.as_str().to_string()),
+            kms_key_id: kms_key_id.map(str::to_string),
+            customer_algorithm: customer_algorithm.map(str::to_string),
+        })
+    }
+}
+
+/// リクエストに設定する暗号化関連のヘッダー
+#[derive(Debug, Default)]
+pub(super) struct SseHeaders {
+    pub(super) server_side_encryption: Option<ServerSideEncryption>,
+    pub(super) kms_key_id: Option<String>,
+    /// 暗号化コンテキスト（JSON を Base64 エンコードした値）
+    pub(super) kms_context: Option<String>,
+    pub(super) customer: CustomerKeyHeaders,
+}
+
+/// SSE-C のヘッダー（アップロード・取得・コピー元の指定で共通）
+#[derive(Debug, Clone, Default)]
+pub(super) struct CustomerKeyHeaders {
+    pub(super) algorithm: Option<String>,
+    /// 鍵を Base64 エンコードした値
+    pub(super) key: Option<String>,
+    /// 鍵の MD5 を Base64 エンコードした値
+    pub(super) key_md5: Option<String>,
+}
+
+/// 暗号化の設定をリクエストヘッダーの値に変換する
+pub(super) fn sse_headers(encryption: Option<&Encryption>) -> Result<SseHeaders, BoxError> {
+    let headers = match encryption {
+        None => SseHeaders::default(),
+        Some(Encryption::S3) => SseHeaders {
+            server_side_encryption: Some(ServerSideEncryption::Aes256),
+            ..SseHeaders::default()
+        },
+        Some(Encryption::Kms { key_id, context }) => SseHeaders {
+            server_side_encryption: Some(ServerSideEncryption::AwsKms),
+            kms_key_id: key_id.clone(),
+            kms_context: if context.is_empty() {
+                None
+            } else {
+                Some(BASE64.encode(serde_json::to_string(context)?))
+            },
+            ..SseHeaders::default()
+        },
+        Some(Encryption::CustomerKey(key)) => {
+            if key.len() != 32 {
+                return Err(format!(
+                    "SSE-C の鍵は 32 バイトである必要があります（{} バイト）",
+                    key.len()
+                )
+                .into());
+            }
+            SseHeaders {
+                customer: CustomerKeyHeaders {
+                    algorithm: Some("AES256".to_string()),
+                    key: Some(BASE64.encode(key)),
+                    key_md5: Some(BASE64.encode(Md5::digest(key))),
+                },
+                ..SseHeaders::default()
+            }
+        }
+    };
+    Ok(headers)
+}
+
+/// SSE-C のヘッダーだけを取り出す（SSE-S3 / SSE-KMS の場合は空）
+/// ※ 取得やパートのアップロードでは SSE-C の鍵だけを指定する
+pub(super) fn customer_key_headers(
+    encryption: Option<&Encryption>,
+) -> Result<CustomerKeyHeaders, BoxError> {
+    Ok(sse_headers(encryption)?.customer)
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+
+/// S3 操作で発生する、このクレート独自のエラー
+#[derive(Debug, Clone, PartialEq)]
+pub enum S3Error {
+    /// 受信したバイト数が Content-Length と一致しない
+    SizeMismatch {
+        key: String,
+        expected: u64,
+        actual: u64,
+    },
+    /// 受信したデータの MD5 が ETag と一致しない
+    ETagMismatch {
+        key: String,
+        expected: String,
+        actual: String,
+    },
+    /// 受信・送信したデータのチェックサムが S3 の値と一致しない
+    ChecksumMismatch {
+        key: String,
+        algorithm: String,
+        expected: String,
+        actual: String,
+    },
+    /// 複合チェックサムが一致せず、パートの分け方の違いと内容の破損を区別できない
+    /// ※ パートサイズが不揃いなマルチパートアップロードで作られたオブジェクトで起こる
+    ChecksumUnverifiable {
+        key: String,
+        algorithm: String,
+        expected_parts: u64,
+        actual_parts: u64,
+    },
+    /// 保護対象のバケットに対して、確認なしで破壊的な操作をしようとした
+    ConfirmationRequired { bucket: String },
+    /// S3 URI やバケット名が不正
+    InvalidUri { uri: String, reason: String },
+}
+
+impl fmt::Display for S3Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            S3Error::SizeMismatch {
+                key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} のサイズが一致しません（期待値: {}, 実際: {}）",
+                key, expected, actual
+            ),
+            S3Error::ETagMismatch {
+                key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} の ETag が一致しません（期待値: {}, 実際: {}）",
+                key, expected, actual
+            ),
+            S3Error::ChecksumMismatch {
+                key,
+                algorithm,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} の {} チェックサムが一致しません（期待値: {}, 実際: {}）",
+                key, algorithm, expected, actual
+            ),
+            S3Error::ChecksumUnverifiable {
+                key,
+                algorithm,
+                expected_parts,
+                actual_parts,
+            } => write!(
+                f,
+                "{} の {} 複合チェックサムが一致しないため検証できません（パートの分け方が異なる可能性があります。S3: {} パート, 計算: {} パート）",
+                key, algorithm, expected_parts, actual_parts
+            ),
+            S3Error::ConfirmationRequired { bucket } => write!(
+                f,
+                "{} は保護対象のバケットです。確認としてバケット名を指定してください",
+                bucket
+            ),
+            S3Error::InvalidUri { uri, reason } => {
+                write!(f, "S3 URI が不正です: {}（{}）", uri, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for S3Error {}
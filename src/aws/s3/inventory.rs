@@ -0,0 +1,349 @@
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt::Write;
+use std::str::FromStr;
+
+use super::bucket::client_for_bucket;
+use super::list::{list_stream, ObjectInfo};
+use super::uri::S3Uri;
+use super::{run_sync, BoxError};
+
+/// インベントリレポートのオプション
+#[derive(Debug, Clone)]
+pub struct InventoryOptions {
+    /// サイズの大きいオブジェクトを何件載せるか
+    pub top_n: usize,
+    /// 経過日数の区切り（昇順）。[30, 90, 365] なら 0-30d / 30-90d / 90-365d / 365d+ に分ける
+    pub age_days: Vec<u32>,
+}
+
+impl Default for InventoryOptions {
+    fn default() -> Self {
+        InventoryOptions {
+            top_n: 10,
+            age_days: vec![30, 90, 365],
+        }
+    }
+}
+
+/// オブジェクト数と合計サイズ
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct InventoryStats {
+    pub objects: u64,
+    pub bytes: u64,
+}
+
+impl InventoryStats {
+    fn add(&mut self, size: u64) {
+        self.objects += 1;
+        self.bytes += size;
+    }
+}
+
+/// サイズの大きいオブジェクト
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LargeObject {
+    pub key: String,
+    pub size: u64,
+    pub storage_class: String,
+    /// 最終更新日時（RFC 3339）
+    pub last_modified: Option<String>,
+}
+
+/// バケット・プレフィックスの集計結果
+/// ※ 内訳はいずれも名前の昇順（経過日数のみ区切りの順）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InventoryReport {
+    /// 集計対象の S3 URI
+    pub uri: String,
+    /// 集計日時（RFC 3339）
+    pub generated_at: String,
+    pub total: InventoryStats,
+    /// ストレージクラスごとの内訳（不明な場合は STANDARD として数える）
+    pub by_storage_class: BTreeMap<String, InventoryStats>,
+    /// 指定したプレフィックス直下の最上位プレフィックスごとの内訳（直下のオブジェクトは "(root)"）
+    pub by_prefix: BTreeMap<String, InventoryStats>,
+    /// 拡張子（小文字）ごとの内訳（拡張子が無いものは "(none)"）
+    pub by_extension: BTreeMap<String, InventoryStats>,
+    /// 最終更新日時からの経過日数ごとの内訳（日時が不明なものは "unknown"）
+    pub by_age: Vec<(String, InventoryStats)>,
+    /// サイズの大きい順のオブジェクト
+    pub largest: Vec<LargeObject>,
+}
+
+impl InventoryReport {
+    /// オブジェクトの一覧から集計する
+    /// ※ prefix は最上位プレフィックスの判定に、now は経過日数の計算に使う
+    pub fn build<I>(
+        uri: &str,
+        prefix: &str,
+        objects: I,
+        now: DateTime<Utc>,
+        options: &InventoryOptions,
+    ) -> Self
+    where
+        I: IntoIterator<Item = ObjectInfo>,
+    {
+        let mut accumulator = Accumulator::new(uri, prefix, now, options);
+        for object in objects {
+            accumulator.add(object);
+        }
+        accumulator.finish()
+    }
+
+    /// JSON（整形済み）に変換する
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// CSV に変換する
+    /// ※ 列は category,name,objects,bytes。category は total / storage_class / prefix / extension / age / largest
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("category,name,objects,bytes\n");
+        let mut row = |category: &str, name: &str, stats: &InventoryStats| {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{}",
+                category,
+                csv_field(name),
+                stats.objects,
+                stats.bytes
+            );
+        };
+        row("total", &self.uri, &self.total);
+        let groups = [
+            ("storage_class", &self.by_storage_class),
+            ("prefix", &self.by_prefix),
+            ("extension", &self.by_extension),
+        ];
+        for (category, group) in groups {
+            for (name, stats) in group {
+                row(category, name, stats);
+            }
+        }
+        for (name, stats) in &self.by_age {
+            row("age", name, stats);
+        }
+        for object in &self.largest {
+            let stats = InventoryStats {
+                objects: 1,
+                bytes: object.size,
+            };
+            row("largest", &object.key, &stats);
+        }
+        csv
+    }
+}
+
+/// バケット・プレフィックス配下を一覧して集計する（非同期版）
+/// ※ uri のキー部分をそのままプレフィックスとして扱う（s3://bucket/ ならバケット全体）
+pub async fn inventory_report_async(
+    client: &Client,
+    uri: &str,
+    options: &InventoryOptions,
+) -> Result<InventoryReport, BoxError> {
+    let s3_uri = S3Uri::from_str(uri)?;
+    let client = client_for_bucket(client, s3_uri.bucket()).await?;
+    // 一覧を取得しながら集計するため、オブジェクト数が多くてもメモリを使い切らない
+    let mut accumulator = Accumulator::new(uri, s3_uri.key(), Utc::now(), options);
+    let mut stream = list_stream(&client, s3_uri.bucket(), s3_uri.key(), None);
+    while let Some(object) = stream.next_object().await {
+        accumulator.add(object?);
+    }
+    Ok(accumulator.finish())
+}
+
+/// バケット・プレフィックス配下を一覧して集計する
+pub fn inventory_report(
+    uri: &str,
+    options: &InventoryOptions,
+) -> Result<InventoryReport, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { inventory_report_async(&s3, uri, options).await })
+}
+
+/// オブジェクトを 1 件ずつ受け取って集計する
+/// ※ 保持するのは集計値と上位 N 件だけ
+struct Accumulator<'a> {
+    report: InventoryReport,
+    prefix: &'a str,
+    now: DateTime<Utc>,
+    options: &'a InventoryOptions,
+    /// 上位 N 件の候補（先頭が次に外す候補）
+    largest: BinaryHeap<Candidate>,
+    /// 受け取ったオブジェクトの数（同じサイズの順位付けに使う）
+    seen: u64,
+}
+
+impl<'a> Accumulator<'a> {
+    fn new(uri: &str, prefix: &'a str, now: DateTime<Utc>, options: &'a InventoryOptions) -> Self {
+        let mut by_age: Vec<(String, InventoryStats)> = age_labels(&options.age_days)
+            .into_iter()
+            .map(|label| (label, InventoryStats::default()))
+            .collect();
+        by_age.push(("unknown".to_string(), InventoryStats::default()));
+
+        Accumulator {
+            report: InventoryReport {
+                uri: uri.to_string(),
+                generated_at: now.to_rfc3339(),
+                total: InventoryStats::default(),
+                by_storage_class: BTreeMap::new(),
+                by_prefix: BTreeMap::new(),
+                by_extension: BTreeMap::new(),
+                by_age,
+                largest: Vec::new(),
+            },
+            prefix,
+            now,
+            options,
+            largest: BinaryHeap::with_capacity(options.top_n + 1),
+            seen: 0,
+        }
+    }
+
+    fn add(&mut self, object: ObjectInfo) {
+        let (report, options) = (&mut self.report, self.options);
+        let size = object.size;
+        report.total.add(size);
+        let storage_class = object.storage_class.as_deref().unwrap_or("STANDARD");
+        report
+            .by_storage_class
+            .entry(storage_class.to_string())
+            .or_default()
+            .add(size);
+        report
+            .by_prefix
+            .entry(top_level_prefix(&object.key, self.prefix))
+            .or_default()
+            .add(size);
+        report
+            .by_extension
+            .entry(extension(&object.key))
+            .or_default()
+            .add(size);
+        let age_index = match object.last_modified {
+            Some(modified) => {
+                let days = (self.now - modified).num_days().max(0) as u64;
+                options
+                    .age_days
+                    .iter()
+                    .position(|&limit| days < limit as u64)
+                    .unwrap_or(options.age_days.len())
+            }
+            None => options.age_days.len() + 1,
+        };
+        report.by_age[age_index].1.add(size);
+
+        // 上位 N 件だけを保持する（同じサイズなら先に見つかったものを残す）
+        self.seen += 1;
+        if options.top_n == 0 {
+            return;
+        }
+        if self.largest.len() < options.top_n {
+            self.largest.push(Candidate {
+                size,
+                seq: self.seen,
+                object,
+            });
+        } else if self.largest.peek().is_some_and(|c| size > c.size) {
+            self.largest.pop();
+            self.largest.push(Candidate {
+                size,
+                seq: self.seen,
+                object,
+            });
+        }
+    }
+
+    fn finish(self) -> InventoryReport {
+        let mut report = self.report;
+        report.largest = self
+            .largest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|c| LargeObject {
+                storage_class: c
+                    .object
+                    .storage_class
+                    .unwrap_or_else(|| "STANDARD".to_string()),
+                last_modified: c.object.last_modified.map(|t| t.to_rfc3339()),
+                key: c.object.key,
+                size: c.size,
+            })
+            .collect();
+        report
+    }
+}
+
+/// 上位 N 件の候補
+/// ※ サイズが小さいほど、同じサイズなら後に見つかったものほど大きい（ヒープの先頭で外される）順序にする
+struct Candidate {
+    size: u64,
+    seq: u64,
+    object: ObjectInfo,
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .size
+            .cmp(&self.size)
+            .then_with(|| self.seq.cmp(&other.seq))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+/// 経過日数の区切りからラベルを作る（例: 0-30d, 30-90d, 90d+）
+fn age_labels(age_days: &[u32]) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut from = 0;
+    for &to in age_days {
+        labels.push(format!("{}-{}d", from, to));
+        from = to;
+    }
+    labels.push(format!("{}d+", from));
+    labels
+}
+
+/// プレフィックスからの相対キーの最初の階層（"a/b/c.txt" なら "a/"）
+fn top_level_prefix(key: &str, prefix: &str) -> String {
+    let rel = key.strip_prefix(prefix).unwrap_or(key);
+    match rel.find('/') {
+        Some(pos) => rel[..=pos].to_string(),
+        None => "(root)".to_string(),
+    }
+}
+
+/// ファイル名の拡張子（小文字、ドットなし）
+fn extension(key: &str) -> String {
+    let name = key.rsplit('/').next().unwrap_or(key);
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => ext.to_lowercase(),
+        _ => "(none)".to_string(),
+    }
+}
+
+/// CSV のフィールドとして必要ならダブルクォートで囲む
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,240 @@
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use tokio::runtime::Runtime;
+
+use super::compression::{Compression, DecodingWriter};
+use super::upload::{put_bytes_async, PutOptions, PutResult};
+use super::uri::S3Uri;
+use super::{run_sync, BoxError};
+use crate::aws::config::make_client;
+
+/// JSON オブジェクトを取得してデシリアライズする（非同期版）
+/// ※ Content-Encoding が gzip / zstd、またはキーが .gz / .zst で終わる場合は展開してから読み込む
+pub async fn get_json_async<T: DeserializeOwned>(
+    client: &Client,
+    uri: &str,
+) -> Result<T, BoxError> {
+    let uri = S3Uri::from_str(uri)?;
+    let resp = client
+        .get_object()
+        .bucket(uri.bucket())
+        .key(uri.key())
+        .send()
+        .await?;
+    let compression = Compression::detect(resp.content_encoding.as_deref(), uri.key());
+    let bytes = resp.body.collect().await?.into_bytes();
+    let mut decoder = DecodingWriter::new(compression, Vec::new())?;
+    decoder.write_all(&bytes)?;
+    Ok(serde_json::from_slice(&decoder.finish()?)?)
+}
+
+/// 値を JSON にシリアライズしてアップロードする（非同期版）
+/// ※ キーが .gz / .zst で終わる場合は圧縮し、Content-Encoding を設定する
+pub async fn put_json_async<T: Serialize + ?Sized>(
+    client: &Client,
+    uri: &str,
+    value: &T,
+) -> Result<PutResult, BoxError> {
+    let body = serde_json::to_vec(value)?;
+    put_text(client, uri, body, "application/json").await
+}
+
+/// JSON Lines のオブジェクトを 1 行ずつデシリアライズするストリームを返す（非同期版）
+/// ※ 受信しながら読み込むため、大きなオブジェクトでも全体をメモリに載せない
+pub async fn read_jsonl_async<T: DeserializeOwned>(
+    client: &Client,
+    uri: &str,
+) -> Result<JsonLinesStream<T>, BoxError> {
+    let uri = S3Uri::from_str(uri)?;
+    let resp = client
+        .get_object()
+        .bucket(uri.bucket())
+        .key(uri.key())
+        .send()
+        .await?;
+    let compression = Compression::detect(resp.content_encoding.as_deref(), uri.key());
+    Ok(JsonLinesStream {
+        uri: uri.to_string(),
+        body: resp.body,
+        decoder: Some(DecodingWriter::new(compression, Vec::new())?),
+        buf: Vec::new(),
+        line_no: 0,
+        done: false,
+        _marker: PhantomData,
+    })
+}
+
+/// 値を 1 行ずつ JSON にシリアライズし、JSON Lines としてアップロードする（非同期版）
+/// ※ キーが .gz / .zst で終わる場合は圧縮し、Content-Encoding を設定する
+/// ※ 全体をメモリ上に組み立ててからアップロードする
+pub async fn write_jsonl_async<I>(
+    client: &Client,
+    uri: &str,
+    values: I,
+) -> Result<PutResult, BoxError>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut body = Vec::new();
+    for value in values {
+        serde_json::to_writer(&mut body, &value)?;
+        body.push(b'\n');
+    }
+    put_text(client, uri, body, "application/x-ndjson").await
+}
+
+/// JSON オブジェクトを取得してデシリアライズする
+pub fn get_json<T: DeserializeOwned>(uri: &str) -> Result<T, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { get_json_async(&s3, uri).await })
+}
+
+/// 値を JSON にシリアライズしてアップロードする
+pub fn put_json<T: Serialize + ?Sized>(
+    uri: &str,
+    value: &T,
+) -> Result<PutResult, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { put_json_async(&s3, uri, value).await })
+}
+
+/// JSON Lines のオブジェクトを 1 行ずつデシリアライズするイテレータを返す
+pub fn read_jsonl<T: DeserializeOwned>(
+    uri: &str,
+) -> Result<JsonLinesIter<T>, Box<dyn std::error::Error>> {
+    let s3 = make_client()?;
+    let rt = Runtime::new()?;
+    let stream = rt
+        .block_on(read_jsonl_async(&s3, uri))
+        .map_err(|e| e as Box<dyn std::error::Error>)?;
+    Ok(JsonLinesIter { rt, stream })
+}
+
+/// 値を 1 行ずつ JSON にシリアライズし、JSON Lines としてアップロードする
+pub fn write_jsonl<I>(uri: &str, values: I) -> Result<PutResult, Box<dyn std::error::Error>>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    run_sync(|s3| async move { write_jsonl_async(&s3, uri, values).await })
+}
+
+/// JSON Lines を 1 行ずつデシリアライズするストリーム
+/// ※ 空行は読み飛ばす
+pub struct JsonLinesStream<T> {
+    uri: String,
+    body: ByteStream,
+    /// 展開器（展開したデータは内部の Vec に溜まる。終端に達したら None）
+    decoder: Option<DecodingWriter<Vec<u8>>>,
+    /// 改行が見つかるまでのデータ
+    buf: Vec<u8>,
+    line_no: usize,
+    done: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> JsonLinesStream<T> {
+    /// 次の行を返す（終端に達した場合は None）
+    pub async fn next(&mut self) -> Option<Result<T, BoxError>> {
+        loop {
+            if let Some(pos) = self.buf.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buf.drain(..=pos).collect();
+                self.line_no += 1;
+                match self.parse(&line) {
+                    Some(result) => return Some(result),
+                    None => continue,
+                }
+            }
+            if self.done {
+                // 最終行が改行で終わっていない場合
+                if self.buf.is_empty() {
+                    return None;
+                }
+                let line = std::mem::take(&mut self.buf);
+                self.line_no += 1;
+                return self.parse(&line);
+            }
+            if let Err(e) = self.fill().await {
+                self.done = true;
+                self.buf.clear();
+                return Some(Err(e));
+            }
+        }
+    }
+
+    /// レスポンスボディから次のチャンクを読み込み、buf に追加する
+    async fn fill(&mut self) -> Result<(), BoxError> {
+        match self.body.try_next().await? {
+            Some(chunk) => {
+                if let Some(decoder) = &mut self.decoder {
+                    decoder.write_all(&chunk)?;
+                    self.buf.append(decoded(decoder));
+                }
+            }
+            None => {
+                if let Some(decoder) = self.decoder.take() {
+                    self.buf.append(&mut decoder.finish()?);
+                }
+                self.done = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// 1 行をデシリアライズする（空行の場合は None）
+    fn parse(&self, line: &[u8]) -> Option<Result<T, BoxError>> {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+        Some(serde_json::from_slice(line).map_err(|e| {
+            format!(
+                "{} の {} 行目を読み込めません: {}",
+                self.uri, self.line_no, e
+            )
+            .into()
+        }))
+    }
+}
+
+/// 展開器がこれまでに展開したデータ（取り出したら空にして使う）
+fn decoded(decoder: &mut DecodingWriter<Vec<u8>>) -> &mut Vec<u8> {
+    match decoder {
+        DecodingWriter::Plain(buf) => buf,
+        DecodingWriter::Gzip(decoder) => decoder.get_mut(),
+        DecodingWriter::Zstd(decoder) => decoder.get_mut(),
+    }
+}
+
+/// JSON Lines を 1 行ずつデシリアライズする同期用イテレータ
+pub struct JsonLinesIter<T> {
+    rt: Runtime,
+    stream: JsonLinesStream<T>,
+}
+
+impl<T: DeserializeOwned> Iterator for JsonLinesIter<T> {
+    type Item = Result<T, BoxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rt.block_on(self.stream.next())
+    }
+}
+
+/// テキストをアップロードする（キーが .gz / .zst で終わる場合は圧縮する）
+async fn put_text(
+    client: &Client,
+    uri: &str,
+    body: Vec<u8>,
+    content_type: &str,
+) -> Result<PutResult, BoxError> {
+    let uri = S3Uri::from_str(uri)?;
+    let options = PutOptions {
+        content_type: Some(content_type.to_string()),
+        compression: Compression::detect(None, uri.key()),
+        ..PutOptions::default()
+    };
+    put_bytes_async(client, uri.bucket(), uri.key(), body, &options).await
+}
@@ -0,0 +1,463 @@
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{
+    AbortIncompleteMultipartUpload, BucketLifecycleConfiguration, ExpirationStatus,
+    LifecycleExpiration, LifecycleRuleAndOperator, LifecycleRuleFilter,
+    NoncurrentVersionExpiration, NoncurrentVersionTransition, Tag, Transition,
+    TransitionStorageClass,
+};
+use aws_sdk_s3::Client;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::bucket::client_for_bucket;
+use super::{run_sync, BoxError};
+
+/// ライフサイクルルール
+/// ※ 対象はプレフィックス・タグ・サイズで指定する（複数指定した場合はすべてに一致するもの）
+/// ※ 日付による期限切れ・移行は扱わない（取得したルールに含まれる場合はエラーにする）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleRule {
+    /// ルール ID（差分はこの ID で突き合わせる）
+    pub id: String,
+    /// 対象のプレフィックス（空ならバケット全体）
+    pub prefix: String,
+    /// 対象のタグ（空なら条件にしない）
+    pub tags: BTreeMap<String, String>,
+    /// このサイズ（バイト）より大きいオブジェクトだけを対象にする
+    pub object_size_greater_than: Option<i64>,
+    /// このサイズ（バイト）より小さいオブジェクトだけを対象にする
+    pub object_size_less_than: Option<i64>,
+    pub enabled: bool,
+    /// 作成から指定日数で現行バージョンを期限切れにする
+    pub expiration_days: Option<i32>,
+    /// 非現行バージョンが無くなった削除マーカーを削除する
+    pub expired_object_delete_marker: bool,
+    /// ストレージクラスの移行（日数の昇順）
+    pub transitions: Vec<LifecycleTransition>,
+    /// 非現行になってから指定日数で削除する
+    pub noncurrent_expiration_days: Option<i32>,
+    /// 削除せずに残す新しい非現行バージョンの数（noncurrent_expiration_days と組み合わせる）
+    pub newer_noncurrent_versions: Option<i32>,
+    /// 非現行バージョンのストレージクラスの移行（非現行になってからの日数の昇順）
+    pub noncurrent_transitions: Vec<LifecycleTransition>,
+    /// 開始から指定日数が経過した未完了のマルチパートアップロードを中止する
+    pub abort_incomplete_multipart_days: Option<i32>,
+}
+
+/// ストレージクラスの移行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleTransition {
+    /// 作成（非現行の移行では非現行になって）からの日数
+    pub days: i32,
+    /// 移行先のストレージクラス（例: "STANDARD_IA", "GLACIER", "DEEP_ARCHIVE"）
+    pub storage_class: String,
+}
+
+impl LifecycleRule {
+    /// 有効なルールを生成する（アクションは各メソッドで追加する）
+    pub fn new(id: &str, prefix: &str) -> Self {
+        LifecycleRule {
+            id: id.to_string(),
+            prefix: prefix.to_string(),
+            tags: BTreeMap::new(),
+            object_size_greater_than: None,
+            object_size_less_than: None,
+            enabled: true,
+            expiration_days: None,
+            expired_object_delete_marker: false,
+            transitions: Vec::new(),
+            noncurrent_expiration_days: None,
+            newer_noncurrent_versions: None,
+            noncurrent_transitions: Vec::new(),
+            abort_incomplete_multipart_days: None,
+        }
+    }
+
+    /// タグが一致するオブジェクトだけを対象にする
+    pub fn with_tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// サイズが min より大きく max より小さいオブジェクトだけを対象にする（None は制限なし）
+    pub fn with_size(mut self, min: Option<i64>, max: Option<i64>) -> Self {
+        self.object_size_greater_than = min;
+        self.object_size_less_than = max;
+        self
+    }
+
+    /// 作成から days 日で期限切れにする
+    pub fn expire_after(mut self, days: i32) -> Self {
+        self.expiration_days = Some(days);
+        self
+    }
+
+    /// 作成から days 日で storage_class に移行する
+    pub fn transition_after(mut self, days: i32, storage_class: &str) -> Self {
+        self.transitions.push(LifecycleTransition {
+            days,
+            storage_class: storage_class.to_string(),
+        });
+        self.transitions.sort_by_key(|t| t.days);
+        self
+    }
+
+    /// 非現行バージョンが無くなった削除マーカーを削除する
+    pub fn expire_delete_markers(mut self) -> Self {
+        self.expired_object_delete_marker = true;
+        self
+    }
+
+    /// 非現行になってから days 日で削除する
+    pub fn expire_noncurrent_after(mut self, days: i32) -> Self {
+        self.noncurrent_expiration_days = Some(days);
+        self
+    }
+
+    /// 非現行バージョンを削除する際に、新しいものから count 個を残す
+    pub fn keep_noncurrent_versions(mut self, count: i32) -> Self {
+        self.newer_noncurrent_versions = Some(count);
+        self
+    }
+
+    /// 非現行になってから days 日で storage_class に移行する
+    pub fn transition_noncurrent_after(mut self, days: i32, storage_class: &str) -> Self {
+        self.noncurrent_transitions.push(LifecycleTransition {
+            days,
+            storage_class: storage_class.to_string(),
+        });
+        self.noncurrent_transitions.sort_by_key(|t| t.days);
+        self
+    }
+
+    /// 開始から days 日が経過した未完了のマルチパートアップロードを中止する
+    pub fn abort_incomplete_multipart_after(mut self, days: i32) -> Self {
+        self.abort_incomplete_multipart_days = Some(days);
+        self
+    }
+
+    /// ルールを無効にする
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    /// SDK のフィルターに変換する
+    /// ※ 条件が 2 つ以上ある場合は And でまとめる
+    fn filter(&self) -> Result<LifecycleRuleFilter, BoxError> {
+        let tags = self
+            .tags
+            .iter()
+            .map(|(key, value)| Tag::builder().key(key).value(value).build())
+            .collect::<Result<Vec<_>, _>>()?;
+        let conditions = usize::from(!self.prefix.is_empty())
+            + tags.len()
+            + usize::from(self.object_size_greater_than.is_some())
+            + usize::from(self.object_size_less_than.is_some());
+        let filter = LifecycleRuleFilter::builder();
+        let filter = if conditions > 1 {
+            filter.and(
+                LifecycleRuleAndOperator::builder()
+                    .set_prefix((!self.prefix.is_empty()).then(|| self.prefix.clone()))
+                    .set_tags((!tags.is_empty()).then_some(tags))
+                    .set_object_size_greater_than(self.object_size_greater_than)
+                    .set_object_size_less_than(self.object_size_less_than)
+                    .build(),
+            )
+        } else if let Some(tag) = tags.into_iter().next() {
+            filter.tag(tag)
+        } else if self.object_size_greater_than.is_some() || self.object_size_less_than.is_some() {
+            filter
+                .set_object_size_greater_than(self.object_size_greater_than)
+                .set_object_size_less_than(self.object_size_less_than)
+        } else {
+            filter.prefix(&self.prefix)
+        };
+        Ok(filter.build())
+    }
+}
+
+impl TryFrom<&LifecycleRule> for aws_sdk_s3::types::LifecycleRule {
+    type Error = BoxError;
+
+    fn try_from(rule: &LifecycleRule) -> Result<Self, BoxError> {
+        let status = if rule.enabled {
+            ExpirationStatus::Enabled
+        } else {
+            ExpirationStatus::Disabled
+        };
+        let transitions = rule
+            .transitions
+            .iter()
+            .map(|t| {
+                Transition::builder()
+                    .days(t.days)
+                    .storage_class(TransitionStorageClass::from(t.storage_class.as_str()))
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let noncurrent_transitions = rule
+            .noncurrent_transitions
+            .iter()
+            .map(|t| {
+                NoncurrentVersionTransition::builder()
+                    .noncurrent_days(t.days)
+                    .storage_class(TransitionStorageClass::from(t.storage_class.as_str()))
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let expiration = (rule.expiration_days.is_some() || rule.expired_object_delete_marker)
+            .then(|| {
+                LifecycleExpiration::builder()
+                    .set_days(rule.expiration_days)
+                    .set_expired_object_delete_marker(
+                        rule.expired_object_delete_marker.then_some(true),
+                    )
+                    .build()
+            });
+        let noncurrent_expiration = rule.noncurrent_expiration_days.map(|days| {
+            NoncurrentVersionExpiration::builder()
+                .noncurrent_days(days)
+                .set_newer_noncurrent_versions(rule.newer_noncurrent_versions)
+                .build()
+        });
+        let sdk_rule = aws_sdk_s3::types::LifecycleRule::builder()
+            .id(&rule.id)
+            .filter(rule.filter()?)
+            .status(status)
+            .set_expiration(expiration)
+            .set_transitions((!transitions.is_empty()).then_some(transitions))
+            .set_noncurrent_version_expiration(noncurrent_expiration)
+            .set_noncurrent_version_transitions(
+                (!noncurrent_transitions.is_empty()).then_some(noncurrent_transitions),
+            )
+            .set_abort_incomplete_multipart_upload(rule.abort_incomplete_multipart_days.map(
+                |days| {
+                    AbortIncompleteMultipartUpload::builder()
+                        .days_after_initiation(days)
+                        .build()
+                },
+            ))
+            .build()?;
+        Ok(sdk_rule)
+    }
+}
+
+/// SDK のルールから変換する
+/// ※ 日付による期限切れ・移行など、LifecycleRule で表せない設定を含むルールはエラーにする
+///   （そのまま置き換えると設定が失われるため）
+impl TryFrom<aws_sdk_s3::types::LifecycleRule> for LifecycleRule {
+    type Error = BoxError;
+
+    fn try_from(rule: aws_sdk_s3::types::LifecycleRule) -> Result<Self, BoxError> {
+        let id = rule.id.unwrap_or_default();
+        let unsupported = |what: &str| -> BoxError {
+            format!("ライフサイクルルール {} は{}を含むため扱えません", id, what).into()
+        };
+
+        let mut result = LifecycleRule::new(&id, "");
+        result.enabled = rule.status == ExpirationStatus::Enabled;
+        // 古い形式のルールはフィルターではなくルール直下にプレフィックスを持つ
+        #[allow(deprecated)]
+        let legacy_prefix = rule.prefix;
+        result.prefix = legacy_prefix.unwrap_or_default();
+        if let Some(filter) = rule.filter {
+            let tags = match filter.and {
+                Some(and) => {
+                    result.object_size_greater_than = and.object_size_greater_than;
+                    result.object_size_less_than = and.object_size_less_than;
+                    result.prefix = and.prefix.unwrap_or_default();
+                    and.tags.unwrap_or_default()
+                }
+                None => {
+                    result.object_size_greater_than = filter.object_size_greater_than;
+                    result.object_size_less_than = filter.object_size_less_than;
+                    if let Some(prefix) = filter.prefix {
+                        result.prefix = prefix;
+                    }
+                    filter.tag.into_iter().collect()
+                }
+            };
+            result.tags = tags.into_iter().map(|t| (t.key, t.value)).collect();
+        }
+
+        if let Some(expiration) = rule.expiration {
+            if expiration.date.is_some() {
+                return Err(unsupported("日付による期限切れ"));
+            }
+            result.expiration_days = expiration.days;
+            result.expired_object_delete_marker =
+                expiration.expired_object_delete_marker.unwrap_or(false);
+        }
+        for t in rule.transitions.unwrap_or_default() {
+            let (Some(days), Some(storage_class)) = (t.days, t.storage_class) else {
+                return Err(unsupported("日付による移行"));
+            };
+            result = result.transition_after(days, storage_class.as_str());
+        }
+        if let Some(expiration) = rule.noncurrent_version_expiration {
+            result.noncurrent_expiration_days = expiration.noncurrent_days;
+            result.newer_noncurrent_versions = expiration.newer_noncurrent_versions;
+        }
+        for t in rule.noncurrent_version_transitions.unwrap_or_default() {
+            if t.newer_noncurrent_versions.is_some() {
+                return Err(unsupported("残す非現行バージョン数を指定した移行"));
+            }
+            let (Some(days), Some(storage_class)) = (t.noncurrent_days, t.storage_class) else {
+                return Err(unsupported("日数を指定しない非現行バージョンの移行"));
+            };
+            result = result.transition_noncurrent_after(days, storage_class.as_str());
+        }
+        result.abort_incomplete_multipart_days = rule
+            .abort_incomplete_multipart_upload
+            .and_then(|a| a.days_after_initiation);
+        Ok(result)
+    }
+}
+
+/// 現在のライフサイクル設定と適用したい設定の差分
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LifecycleDiff {
+    /// 追加されるルール
+    pub added: Vec<LifecycleRule>,
+    /// 削除されるルール
+    pub removed: Vec<LifecycleRule>,
+    /// 変更されるルール（変更前, 変更後）
+    pub changed: Vec<(LifecycleRule, LifecycleRule)>,
+}
+
+impl LifecycleDiff {
+    /// 差分が無いかどうか
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// 1 ルール 1 行で「+ 追加」「- 削除」「~ 変更」を表示する
+impl fmt::Display for LifecycleDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for rule in &self.added {
+            writeln!(f, "+ {} ({:?})", rule.id, rule.prefix)?;
+        }
+        for rule in &self.removed {
+            writeln!(f, "- {} ({:?})", rule.id, rule.prefix)?;
+        }
+        for (before, after) in &self.changed {
+            writeln!(f, "~ {}: {:?} -> {:?}", after.id, before, after)?;
+        }
+        Ok(())
+    }
+}
+
+/// ルールを ID で突き合わせて差分を求める
+pub fn diff_lifecycle(current: &[LifecycleRule], desired: &[LifecycleRule]) -> LifecycleDiff {
+    let mut diff = LifecycleDiff::default();
+    for rule in desired {
+        match current.iter().find(|c| c.id == rule.id) {
+            None => diff.added.push(rule.clone()),
+            Some(c) if c != rule => diff.changed.push((c.clone(), rule.clone())),
+            Some(_) => {}
+        }
+    }
+    diff.removed = current
+        .iter()
+        .filter(|c| !desired.iter().any(|d| d.id == c.id))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// バケットのライフサイクルルールを取得する（非同期版）
+/// ※ 設定が無い場合は空の一覧を返す
+/// ※ LifecycleRule で表せないルールが含まれる場合はエラーを返す
+pub async fn get_lifecycle_rules_async(
+    client: &Client,
+    bucket: &str,
+) -> Result<Vec<LifecycleRule>, BoxError> {
+    let client = client_for_bucket(client, bucket).await?;
+    match client
+        .get_bucket_lifecycle_configuration()
+        .bucket(bucket)
+        .send()
+        .await
+    {
+        Ok(resp) => resp
+            .rules
+            .unwrap_or_default()
+            .into_iter()
+            .map(LifecycleRule::try_from)
+            .collect(),
+        Err(e) if e.code() == Some("NoSuchLifecycleConfiguration") => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// バケットのライフサイクルルールを置き換える（非同期版）
+/// ※ 空の一覧を指定するとライフサイクル設定を削除する
+pub async fn put_lifecycle_rules_async(
+    client: &Client,
+    bucket: &str,
+    rules: &[LifecycleRule],
+) -> Result<(), BoxError> {
+    let client = client_for_bucket(client, bucket).await?;
+    if rules.is_empty() {
+        client
+            .delete_bucket_lifecycle()
+            .bucket(bucket)
+            .send()
+            .await?;
+        return Ok(());
+    }
+    let rules = rules
+        .iter()
+        .map(aws_sdk_s3::types::LifecycleRule::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    let configuration = BucketLifecycleConfiguration::builder()
+        .set_rules(Some(rules))
+        .build()?;
+    client
+        .put_bucket_lifecycle_configuration()
+        .bucket(bucket)
+        .lifecycle_configuration(configuration)
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// 現在の設定との差分を求め、差分があればライフサイクルルールを置き換える（非同期版）
+/// ※ dry_run の場合は差分を返すだけで変更しない
+pub async fn apply_lifecycle_rules_async(
+    client: &Client,
+    bucket: &str,
+    rules: &[LifecycleRule],
+    dry_run: bool,
+) -> Result<LifecycleDiff, BoxError> {
+    let current = get_lifecycle_rules_async(client, bucket).await?;
+    let diff = diff_lifecycle(&current, rules);
+    if !dry_run && !diff.is_empty() {
+        put_lifecycle_rules_async(client, bucket, rules).await?;
+    }
+    Ok(diff)
+}
+
+/// バケットのライフサイクルルールを取得する
+pub fn get_lifecycle_rules(bucket: &str) -> Result<Vec<LifecycleRule>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { get_lifecycle_rules_async(&s3, bucket).await })
+}
+
+/// バケットのライフサイクルルールを置き換える
+pub fn put_lifecycle_rules(
+    bucket: &str,
+    rules: &[LifecycleRule],
+) -> Result<(), Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { put_lifecycle_rules_async(&s3, bucket, rules).await })
+}
+
+/// 現在の設定との差分を求め、差分があればライフサイクルルールを置き換える
+pub fn apply_lifecycle_rules(
+    bucket: &str,
+    rules: &[LifecycleRule],
+    dry_run: bool,
+) -> Result<LifecycleDiff, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { apply_lifecycle_rules_async(&s3, bucket, rules, dry_run).await })
+}
@@ -0,0 +1,215 @@
+use aws_sdk_s3::types::Object;
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use tokio::runtime::Runtime;
+
+use super::pattern::KeyPattern;
+use super::{to_chrono, trim_etag, BoxError};
+use crate::aws::config::make_client;
+
+/// オブジェクトの情報を保持する構造体
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectInfo {
+    /// オブジェクトキー
+    pub key: String,
+    /// サイズ（バイト）
+    pub size: u64,
+    /// ETag（前後のダブルクォートは取り除いた値）
+    pub e_tag: Option<String>,
+    /// 最終更新日時
+    pub last_modified: Option<DateTime<Utc>>,
+    /// ストレージクラス（STANDARD, GLACIER など）
+    pub storage_class: Option<String>,
+}
+
+impl From<Object> for ObjectInfo {
+    fn from(object: Object) -> Self {
+        ObjectInfo {
+            key: object.key.unwrap_or_default(),
+            size: object.size.unwrap_or(0).max(0) as u64,
+            e_tag: object.e_tag.as_deref().map(trim_etag),
+            last_modified: object.last_modified.as_ref().and_then(to_chrono),
+            storage_class: object.storage_class.map(|c| c.as_str().to_string()),
+        }
+    }
+}
+
+/// 一覧取得で返される要素
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListEntry {
+    /// オブジェクト
+    Object(ObjectInfo),
+    /// 区切り文字モードでの「ディレクトリ」（CommonPrefixes）
+    Prefix(String),
+}
+
+/// ListObjectsV2 をページ単位で遅延取得する非同期ストリーム
+/// ※ 取得済みのページを使い切った時点で次のページを要求する
+pub struct ListStream {
+    client: Client,
+    bucket: String,
+    prefix: String,
+    delimiter: Option<String>,
+    /// 指定した場合は一致するオブジェクトだけを返す
+    pattern: Option<KeyPattern>,
+    continuation_token: Option<String>,
+    finished: bool,
+    buffer: VecDeque<ListEntry>,
+}
+
+impl ListStream {
+    /// 次の要素を取得する（すべて取得し終えたら None）
+    pub async fn next(&mut self) -> Option<Result<ListEntry, BoxError>> {
+        while self.buffer.is_empty() {
+            if self.finished {
+                return None;
+            }
+            if let Err(e) = self.fetch_page().await {
+                // エラー後に同じページを再取得し続けないよう打ち切る
+                self.finished = true;
+                return Some(Err(e));
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+
+    /// 次のオブジェクトを取得する（Prefix は読み飛ばす）
+    pub async fn next_object(&mut self) -> Option<Result<ObjectInfo, BoxError>> {
+        loop {
+            match self.next().await? {
+                Ok(ListEntry::Object(object)) => return Some(Ok(object)),
+                Ok(ListEntry::Prefix(_)) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// 1 ページ分を取得してバッファに積む
+    async fn fetch_page(&mut self) -> Result<(), BoxError> {
+        let page = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(&self.prefix)
+            .set_delimiter(self.delimiter.clone())
+            .set_continuation_token(self.continuation_token.take())
+            .send()
+            .await?;
+
+        for common_prefix in page.common_prefixes.unwrap_or_default() {
+            if let Some(prefix) = common_prefix.prefix {
+                self.buffer.push_back(ListEntry::Prefix(prefix));
+            }
+        }
+        for object in page.contents.unwrap_or_default() {
+            let object = ObjectInfo::from(object);
+            if self
+                .pattern
+                .as_ref()
+                .map_or(true, |p| p.is_match(&object.key))
+            {
+                self.buffer.push_back(ListEntry::Object(object));
+            }
+        }
+
+        self.continuation_token = page.next_continuation_token;
+        if !page.is_truncated.unwrap_or(false) || self.continuation_token.is_none() {
+            self.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// 非同期コードから使う一覧取得ストリームを生成する
+/// ※ `delimiter` を指定すると、その区切りより下の階層は ListEntry::Prefix にまとめられる
+pub fn list_stream(
+    client: &Client,
+    bucket: &str,
+    prefix: &str,
+    delimiter: Option<&str>,
+) -> ListStream {
+    ListStream {
+        client: client.clone(),
+        bucket: bucket.to_string(),
+        prefix: prefix.to_string(),
+        delimiter: delimiter.map(str::to_string),
+        pattern: None,
+        continuation_token: None,
+        finished: false,
+        buffer: VecDeque::new(),
+    }
+}
+
+/// パターンに一致するオブジェクトだけを返す一覧取得ストリームを生成する
+/// ※ パターン先頭の固定部分で一覧し、残りは取得したキーに対して照合する
+pub fn list_stream_matching(client: &Client, bucket: &str, pattern: &KeyPattern) -> ListStream {
+    ListStream {
+        pattern: Some(pattern.clone()),
+        ..list_stream(client, bucket, pattern.prefix(), None)
+    }
+}
+
+/// 同期コードから使う一覧取得イテレータ
+/// ※ 内部に Tokio ランタイムを持ち、ページが必要になった時だけ取得する
+pub struct ListIter {
+    rt: Runtime,
+    stream: ListStream,
+}
+
+impl Iterator for ListIter {
+    type Item = Result<ListEntry, BoxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rt.block_on(self.stream.next())
+    }
+}
+
+/// プレフィックス配下のオブジェクトを遅延取得するイテレータを返す
+pub fn list_objects(
+    bucket: &str,
+    prefix: &str,
+) -> Result<impl Iterator<Item = Result<ObjectInfo, BoxError>>, Box<dyn std::error::Error>> {
+    let iter = make_list_iter(bucket, prefix, None)?;
+    Ok(only_objects(iter))
+}
+
+/// パターンに一致するオブジェクトを遅延取得するイテレータを返す
+/// 例: KeyPattern::from_uri("s3://bucket/logs/2026-*/**/*.json") で作ったパターン
+pub fn list_objects_matching(
+    bucket: &str,
+    pattern: &KeyPattern,
+) -> Result<impl Iterator<Item = Result<ObjectInfo, BoxError>>, Box<dyn std::error::Error>> {
+    let mut iter = make_list_iter(bucket, pattern.prefix(), None)?;
+    iter.stream.pattern = Some(pattern.clone());
+    Ok(only_objects(iter))
+}
+
+/// プレフィックス直下のオブジェクトと「ディレクトリ」を遅延取得するイテレータを返す
+/// ※ 区切り文字は "/" を使う
+pub fn list_directory(bucket: &str, prefix: &str) -> Result<ListIter, Box<dyn std::error::Error>> {
+    make_list_iter(bucket, prefix, Some("/"))
+}
+
+/// Prefix を読み飛ばしてオブジェクトだけを返す
+fn only_objects(iter: ListIter) -> impl Iterator<Item = Result<ObjectInfo, BoxError>> {
+    iter.filter_map(|entry| match entry {
+        Ok(ListEntry::Object(object)) => Some(Ok(object)),
+        Ok(ListEntry::Prefix(_)) => None,
+        Err(e) => Some(Err(e)),
+    })
+}
+
+/// 同期用イテレータを生成する
+fn make_list_iter(
+    bucket: &str,
+    prefix: &str,
+    delimiter: Option<&str>,
+) -> Result<ListIter, Box<dyn std::error::Error>> {
+    let s3 = make_client()?;
+    let rt = Runtime::new()?;
+    Ok(ListIter {
+        rt,
+        stream: list_stream(&s3, bucket, prefix, delimiter),
+    })
+}
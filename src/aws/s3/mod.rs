@@ -0,0 +1,177 @@
+mod bandwidth;
+mod bucket;
+mod cache;
+mod checksum;
+mod compression;
+mod copy;
+mod delete;
+mod diff;
+mod download;
+mod encryption;
+mod error;
+mod inventory;
+#[cfg(feature = "use_serde")]
+mod json;
+mod lifecycle;
+mod list;
+mod multipart;
+mod object;
+mod object_io;
+mod pattern;
+mod presign;
+mod progress;
+mod ranged;
+mod store;
+mod sync;
+mod upload;
+mod uri;
+mod versions;
+
+pub use bandwidth::{bandwidth_limit, set_bandwidth_limit, throttled_byte_stream};
+pub use bucket::{
+    create_bucket, create_bucket_async, delete_bucket, delete_bucket_async, empty_bucket,
+    empty_bucket_async, is_versioning_enabled, is_versioning_enabled_async, list_buckets,
+    list_buckets_async, set_versioning, set_versioning_async, BucketInfo,
+};
+pub use cache::{CacheEntry, CacheOptions, S3Cache};
+pub use checksum::ChecksumAlgorithm;
+pub use compression::Compression;
+pub use copy::{
+    copy_matching, copy_matching_async, copy_object, copy_object_async, copy_prefix,
+    copy_prefix_async, move_matching, move_matching_async, move_object, move_object_async,
+    move_prefix, move_prefix_async, CopyOptions, COPY_OBJECT_LIMIT,
+};
+pub use delete::{
+    delete_matching, delete_matching_async, delete_object, delete_object_async, delete_objects,
+    delete_objects_async, delete_prefix, delete_prefix_async, is_protected, DeleteFailure,
+    DeleteGuard, DeletePrefixOptions, DeleteReport, ObjectId,
+};
+pub use diff::{
+    diff_prefixes, diff_prefixes_async, DiffEntry, DiffKind, DiffOptions, DiffSide, PrefixDiff,
+};
+pub use download::{
+    download_matching, download_matching_async, get_object_bytes, get_object_bytes_async,
+    get_object_to_file, get_object_to_file_async, get_object_to_writer, get_object_to_writer_async,
+    DownloadInfo, GetOptions,
+};
+pub use encryption::{Encryption, EncryptionInfo};
+pub use error::S3Error;
+pub use inventory::{
+    inventory_report, inventory_report_async, InventoryOptions, InventoryReport, InventoryStats,
+    LargeObject,
+};
+#[cfg(feature = "use_serde")]
+pub use json::{
+    get_json, get_json_async, put_json, put_json_async, read_jsonl, read_jsonl_async, write_jsonl,
+    write_jsonl_async, JsonLinesIter, JsonLinesStream,
+};
+pub use lifecycle::{
+    apply_lifecycle_rules, apply_lifecycle_rules_async, diff_lifecycle, get_lifecycle_rules,
+    get_lifecycle_rules_async, put_lifecycle_rules, put_lifecycle_rules_async, LifecycleDiff,
+    LifecycleRule, LifecycleTransition,
+};
+pub use list::{
+    list_directory, list_objects, list_objects_matching, list_stream, list_stream_matching,
+    ListEntry, ListIter, ListStream, ObjectInfo,
+};
+pub use multipart::{
+    upload_file_multipart, upload_file_multipart_async, MultipartOptions, MIN_PART_SIZE,
+};
+pub use object::{
+    delete_object_tags, delete_object_tags_async, exists, exists_async, get_object_tags,
+    get_object_tags_async, head_object, head_object_async, put_object_tags, put_object_tags_async,
+    ObjectMetadata, RestoreStatus,
+};
+pub use object_io::{S3ObjectReader, S3ObjectWriter};
+pub use pattern::KeyPattern;
+pub use presign::{
+    presign_get, presign_get_async, presign_put, presign_put_async, PresignOptions, PresignedUrl,
+};
+pub use progress::{ProgressEvent, ProgressKind, ProgressReporter, TransferProgress};
+pub use ranged::{download_file_ranged, download_file_ranged_async, RangedOptions};
+pub use store::{LocalStore, MemoryStore, ObjectStore, S3Store};
+pub use sync::{sync_down, sync_down_async, sync_up, sync_up_async, SyncAction, SyncOptions};
+pub use upload::{
+    guess_content_type, put_bytes, put_bytes_async, put_file, put_file_async, PutOptions,
+    PutResult, MULTIPART_THRESHOLD,
+};
+pub use uri::{validate_bucket_name, S3Uri};
+pub use versions::{
+    get_object_version_bytes, get_object_version_bytes_async, get_object_version_to_file,
+    get_object_version_to_file_async, list_key_versions, list_key_versions_async, list_versions,
+    list_versions_async, restore_plan, restore_prefix_to, restore_prefix_to_async, ObjectVersion,
+    RestoreOptions,
+};
+
+use aws_sdk_s3::primitives::DateTime as AwsDateTime;
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use std::future::Future;
+use tokio::runtime::Runtime;
+use tokio::task::JoinSet;
+
+use crate::aws::config::make_client;
+
+/// S3 操作で返すエラー型
+/// ※ 非同期タスク間で受け渡せるよう Send + Sync を付けている
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 同期関数から、S3 クライアントを受け取る非同期処理を実行する
+fn run_sync<T, F, Fut>(f: F) -> Result<T, Box<dyn std::error::Error>>
+where
+    F: FnOnce(Client) -> Fut,
+    Fut: Future<Output = Result<T, BoxError>>,
+{
+    let s3 = make_client()?;
+    let rt = Runtime::new()?;
+    rt.block_on(f(s3))
+        .map_err(|e| -> Box<dyn std::error::Error> { e })
+}
+
+/// 非同期タスクを最大 concurrency 個まで並行して実行する
+/// ※ 完了したタスクの結果は完了順に on_done へ渡す
+/// ※ 1 つでも失敗したら残りのタスクを中止してエラーを返す
+async fn run_concurrently<T, Fut, I, F>(
+    tasks: I,
+    concurrency: usize,
+    mut on_done: F,
+) -> Result<(), BoxError>
+where
+    I: IntoIterator<Item = Fut>,
+    Fut: Future<Output = Result<T, BoxError>> + Send + 'static,
+    T: Send + 'static,
+    F: FnMut(T) -> Result<(), BoxError>,
+{
+    let mut pending = tasks.into_iter();
+    let mut running = JoinSet::new();
+    loop {
+        while running.len() < concurrency.max(1) {
+            let Some(task) = pending.next() else {
+                break;
+            };
+            running.spawn(task);
+        }
+
+        let Some(joined) = running.join_next().await else {
+            return Ok(());
+        };
+        let result = joined
+            .map_err(BoxError::from)
+            .and_then(|r| r)
+            .and_then(&mut on_done);
+        if let Err(e) = result {
+            running.abort_all();
+            return Err(e);
+        }
+    }
+}
+
+/// AWS SDK の日時を chrono の日時に変換する
+fn to_chrono(date: &AwsDateTime) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(date.secs(), date.subsec_nanos())
+}
+
+/// ETag の前後に付いているダブルクォートを取り除く
+fn trim_etag(e_tag: &str) -> String {
+    e_tag.trim_matches('"').to_string()
+}
@@ -0,0 +1,577 @@
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use aws_sdk_s3::Client;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::bandwidth::upload_byte_stream;
+use super::checksum::{checksum_headers, composite_from_parts, verify_checksum, ChecksumAlgorithm};
+use super::download::temp_path;
+use super::encryption::{customer_key_headers, sse_headers, CustomerKeyHeaders, EncryptionInfo};
+use super::progress::ProgressReporter;
+use super::upload::{infer_content_type, put_bytes_async, PutOptions, PutResult};
+use super::{run_concurrently, run_sync, trim_etag, BoxError};
+
+/// S3 が受け付けるパートサイズの下限（最後のパートを除く）
+pub const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// 1 回のマルチパートアップロードで使えるパート数の上限
+const MAX_PARTS: u64 = 10_000;
+
+/// S3 が受け付けるパートサイズの上限
+const MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// StreamingUpload でパートサイズを倍にする間隔（パート数）
+/// ※ 5 MiB から始めても、上限のパート数までに約 5 TiB（オブジェクトの上限）を送れる
+const PARTS_PER_DOUBLING: usize = 1000;
+
+/// マルチパートアップロードのオプション
+/// ※ メモリ使用量はおおよそ part_size × concurrency になる
+#[derive(Debug, Clone)]
+pub struct MultipartOptions {
+    /// パートサイズ（バイト）。MIN_PART_SIZE 未満は切り上げる
+    pub part_size: u64,
+    /// 同時にアップロードするパート数
+    pub concurrency: usize,
+    /// 再開用の状態ファイル。指定すると失敗時に中止せず、次回その続きから再開する
+    pub state_file: Option<PathBuf>,
+    /// Content-Type などのオブジェクト属性
+    pub put: PutOptions,
+}
+
+impl Default for MultipartOptions {
+    fn default() -> Self {
+        MultipartOptions {
+            part_size: 64 * 1024 * 1024,
+            concurrency: 4,
+            state_file: None,
+            put: PutOptions::default(),
+        }
+    }
+}
+
+/// 再開用に保存するアップロードの状態
+#[derive(Debug, Serialize, Deserialize)]
+struct UploadState {
+    bucket: String,
+    key: String,
+    upload_id: String,
+    file_size: u64,
+    file_modified: SystemTime,
+    part_size: u64,
+    #[serde(default)]
+    checksum: Option<ChecksumAlgorithm>,
+    parts: Vec<PartState>,
+}
+
+/// 完了済みパートの情報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct PartState {
+    pub(super) part_number: i32,
+    pub(super) e_tag: String,
+    /// パートのチェックサム（PutOptions.checksum を指定した場合のみ）
+    #[serde(default)]
+    pub(super) checksum: Option<String>,
+}
+
+/// ファイルをマルチパートでアップロードする（非同期版）
+/// ※ 状態ファイルを指定しない場合、失敗時はアップロードを中止（Abort）する
+/// ※ 圧縮（PutOptions.compression）には対応しない。圧縮する場合は put_file を使う
+pub async fn upload_file_multipart_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &MultipartOptions,
+) -> Result<PutResult, BoxError> {
+    if options.put.compression.is_some() {
+        return Err(
+            "マルチパートアップロードでは圧縮を指定できません（put_file を使ってください）".into(),
+        );
+    }
+    let metadata = fs::metadata(path)?;
+    let file_size = metadata.len();
+    let file_modified = metadata.modified()?;
+    let part_size = effective_part_size(file_size, options.part_size);
+
+    // 状態ファイルが今回のアップロードと一致し、S3 側にアップロードが残っていれば、その続きから再開する
+    // ※ ファイルやパートサイズが変わっていた場合は、前回のアップロードを中止して最初からやり直す
+    let mut resumed = None;
+    if let Some(state) = options.state_file.as_deref().and_then(load_state) {
+        let matches = state.bucket == bucket
+            && state.key == key
+            && state.file_size == file_size
+            && state.file_modified == file_modified
+            && state.part_size == part_size
+            && state.checksum == options.put.checksum;
+        if !matches {
+            abort_upload(client, &state.bucket, &state.key, &state.upload_id).await;
+        } else if upload_exists(client, &state, &options.put).await? {
+            resumed = Some(state);
+        }
+    }
+    let mut state = match resumed {
+        Some(state) => state,
+        None => UploadState {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            upload_id: create_upload(
+                client,
+                bucket,
+                key,
+                infer_content_type(&options.put, Some(path), key),
+                &options.put,
+            )
+            .await?,
+            file_size,
+            file_modified,
+            part_size,
+            checksum: options.put.checksum,
+            parts: Vec::new(),
+        },
+    };
+    if let Some(state_file) = &options.state_file {
+        save_state(state_file, &state)?;
+    }
+    if let Some(progress) = &options.put.progress {
+        progress.file_started(key, file_size);
+        // 再開した場合は、前回までに送信したパートを転送済みとして数える
+        for part in &state.parts {
+            let offset = (part.part_number as u64 - 1) * part_size;
+            progress.transferred(part_size.min(file_size - offset));
+        }
+    }
+
+    let result = upload_parts(client, path, &mut state, options).await;
+    let result = match result {
+        Ok(()) => {
+            complete_upload(
+                client,
+                &state.bucket,
+                &state.key,
+                &state.upload_id,
+                state.parts.clone(),
+                &options.put,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+
+    if let (Ok(_), Some(progress)) = (&result, &options.put.progress) {
+        progress.file_finished(key);
+    }
+    match (&result, &options.state_file) {
+        (Ok(_), Some(state_file)) => {
+            let _ = fs::remove_file(state_file);
+        }
+        (Err(_), None) => {
+            // 再開しない場合は、課金対象のパートが残らないよう中止する
+            abort_upload(client, bucket, key, &state.upload_id).await;
+        }
+        _ => {}
+    }
+    result
+}
+
+/// ファイルをマルチパートでアップロードする
+pub fn upload_file_multipart(
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &MultipartOptions,
+) -> Result<PutResult, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { upload_file_multipart_async(&s3, bucket, key, path, options).await })
+}
+
+/// パート数が上限を超えないよう、パートサイズを調整する
+pub(super) fn effective_part_size(file_size: u64, part_size: u64) -> u64 {
+    let min_for_limit = file_size.div_ceil(MAX_PARTS);
+    part_size.max(MIN_PART_SIZE).max(min_for_limit)
+}
+
+/// CreateMultipartUpload を実行してアップロード ID を返す
+pub(super) async fn create_upload(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    content_type: Option<String>,
+    options: &PutOptions,
+) -> Result<String, BoxError> {
+    let metadata = (!options.metadata.is_empty()).then(|| options.metadata.clone());
+    let sse = sse_headers(options.encryption.as_ref())?;
+    let resp = client
+        .create_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .set_content_type(content_type)
+        .set_metadata(metadata)
+        .set_cache_control(options.cache_control.clone())
+        .set_content_encoding(options.content_encoding.clone())
+        .set_storage_class(options.storage_class.clone())
+        .set_acl(options.acl.clone())
+        .set_server_side_encryption(sse.server_side_encryption)
+        .set_ssekms_key_id(sse.kms_key_id)
+        .set_ssekms_encryption_context(sse.kms_context)
+        .set_sse_customer_algorithm(sse.customer.algorithm)
+        .set_sse_customer_key(sse.customer.key)
+        .set_sse_customer_key_md5(sse.customer.key_md5)
+        .set_checksum_algorithm(options.checksum.map(ChecksumAlgorithm::to_sdk))
+        .send()
+        .await?;
+    Ok(resp.upload_id.ok_or("アップロード ID が返されていません")?)
+}
+
+/// マルチパートアップロードを中止する（失敗しても無視する）
+async fn abort_upload(client: &Client, bucket: &str, key: &str, upload_id: &str) {
+    let _ = client
+        .abort_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .send()
+        .await;
+}
+
+/// 状態ファイルのアップロードが S3 側に残っているかどうか
+/// ※ 完了・中止済み、または期限切れで削除された場合は NoSuchUpload になる
+async fn upload_exists(
+    client: &Client,
+    state: &UploadState,
+    options: &PutOptions,
+) -> Result<bool, BoxError> {
+    let customer = customer_key_headers(options.encryption.as_ref())?;
+    let result = client
+        .list_parts()
+        .bucket(&state.bucket)
+        .key(&state.key)
+        .upload_id(&state.upload_id)
+        .max_parts(1)
+        .set_sse_customer_algorithm(customer.algorithm)
+        .set_sse_customer_key(customer.key)
+        .set_sse_customer_key_md5(customer.key_md5)
+        .send()
+        .await;
+    match result {
+        Ok(_) => Ok(true),
+        Err(e) if e.code() == Some("NoSuchUpload") => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 未完了のパートを並行してアップロードする
+/// ※ パートが 1 つ完了するたびに状態ファイルを更新する
+async fn upload_parts(
+    client: &Client,
+    path: &Path,
+    state: &mut UploadState,
+    options: &MultipartOptions,
+) -> Result<(), BoxError> {
+    let part_count = state.file_size.div_ceil(state.part_size).max(1);
+    let pending: Vec<i32> = (1..=part_count as i32)
+        .filter(|n| !state.parts.iter().any(|p| p.part_number == *n))
+        .collect();
+
+    let (bucket, key, upload_id) = (
+        state.bucket.clone(),
+        state.key.clone(),
+        state.upload_id.clone(),
+    );
+    let (part_size, file_size) = (state.part_size, state.file_size);
+    let customer = customer_key_headers(options.put.encryption.as_ref())?;
+    let checksum = state.checksum;
+    let tasks = pending.into_iter().map(|part_number| {
+        let offset = (part_number as u64 - 1) * part_size;
+        let length = part_size.min(file_size - offset);
+        upload_part(
+            client.clone(),
+            bucket.clone(),
+            key.clone(),
+            upload_id.clone(),
+            path.to_path_buf(),
+            part_number,
+            offset,
+            length,
+            customer.clone(),
+            checksum,
+            options.put.progress.clone(),
+        )
+    });
+
+    run_concurrently(tasks, options.concurrency, |part| {
+        state.parts.push(part);
+        if let Some(state_file) = &options.state_file {
+            save_state(state_file, state)?;
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// ファイルの指定範囲を読み込んで 1 パート分アップロードする
+#[allow(clippy::too_many_arguments)]
+async fn upload_part(
+    client: Client,
+    bucket: String,
+    key: String,
+    upload_id: String,
+    path: PathBuf,
+    part_number: i32,
+    offset: u64,
+    length: u64,
+    customer: CustomerKeyHeaders,
+    checksum: Option<ChecksumAlgorithm>,
+    progress: Option<ProgressReporter>,
+) -> Result<PartState, BoxError> {
+    let mut file = tokio::fs::File::open(&path).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    let mut buf = vec![0u8; length as usize];
+    file.read_exact(&mut buf).await?;
+    let checksum = checksum.map(|a| (a, a.checksum(&buf)));
+    let headers = checksum_headers(checksum.as_ref());
+
+    let resp = client
+        .upload_part()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .part_number(part_number)
+        .body(upload_byte_stream(ByteStream::from(buf), progress.as_ref()))
+        .set_sse_customer_algorithm(customer.algorithm)
+        .set_sse_customer_key(customer.key)
+        .set_sse_customer_key_md5(customer.key_md5)
+        .set_checksum_crc32_c(headers.crc32c)
+        .set_checksum_sha256(headers.sha256)
+        .send()
+        .await?;
+    Ok(PartState {
+        part_number,
+        e_tag: resp.e_tag.ok_or("パートの ETag が返されていません")?,
+        checksum: checksum.map(|(_, value)| value),
+    })
+}
+
+/// サイズが事前にわからないデータを、先頭から順に 1 パートずつ送るマルチパートアップロード
+/// ※ 最初のパートを送る時にアップロードを開始する
+/// ※ 失敗した場合は abort() で中止する
+/// ※ 全体のサイズがわからないため、PARTS_PER_DOUBLING パートごとにパートサイズを倍にする
+#[derive(Clone)]
+pub(super) struct StreamingUpload {
+    bucket: String,
+    key: String,
+    options: PutOptions,
+    part_size: u64,
+    upload_id: Option<String>,
+    parts: Vec<PartState>,
+}
+
+impl StreamingUpload {
+    pub(super) fn new(bucket: &str, key: &str, options: &PutOptions, part_size: u64) -> Self {
+        StreamingUpload {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            options: options.clone(),
+            part_size: part_size.max(MIN_PART_SIZE),
+            upload_id: None,
+            parts: Vec::new(),
+        }
+    }
+
+    /// 最初のパートの大きさを変更する（MIN_PART_SIZE 未満は MIN_PART_SIZE になる）
+    pub(super) fn set_part_size(&mut self, part_size: u64) {
+        self.part_size = part_size.max(MIN_PART_SIZE);
+    }
+
+    /// 次に送るパートの大きさ
+    pub(super) fn part_size(&self) -> usize {
+        let doublings = (self.parts.len() / PARTS_PER_DOUBLING) as u32;
+        self.part_size
+            .saturating_mul(1 << doublings)
+            .min(MAX_PART_SIZE) as usize
+    }
+
+    /// 1 パート分のデータを送る
+    /// ※ サイズは事前にわからないため、パートを送るたびに進捗の合計へ加える
+    pub(super) async fn send_part(
+        &mut self,
+        client: &Client,
+        buf: Vec<u8>,
+    ) -> Result<(), BoxError> {
+        if self.parts.len() as u64 >= MAX_PARTS {
+            return Err(format!("パート数が上限（{}）を超えます", MAX_PARTS).into());
+        }
+        let upload_id = match &self.upload_id {
+            Some(upload_id) => upload_id.clone(),
+            None => {
+                let content_type = infer_content_type(&self.options, None, &self.key);
+                let upload_id =
+                    create_upload(client, &self.bucket, &self.key, content_type, &self.options)
+                        .await?;
+                if let Some(progress) = &self.options.progress {
+                    progress.file_started(&self.key, 0);
+                }
+                self.upload_id = Some(upload_id.clone());
+                upload_id
+            }
+        };
+        if let Some(progress) = &self.options.progress {
+            progress.add_total(buf.len() as u64);
+        }
+        let part_number = self.parts.len() as i32 + 1;
+        let customer = customer_key_headers(self.options.encryption.as_ref())?;
+        let checksum = self.options.checksum.map(|a| (a, a.checksum(&buf)));
+        let headers = checksum_headers(checksum.as_ref());
+        let resp = client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(upload_byte_stream(
+                ByteStream::from(buf),
+                self.options.progress.as_ref(),
+            ))
+            .set_sse_customer_algorithm(customer.algorithm)
+            .set_sse_customer_key(customer.key)
+            .set_sse_customer_key_md5(customer.key_md5)
+            .set_checksum_crc32_c(headers.crc32c)
+            .set_checksum_sha256(headers.sha256)
+            .send()
+            .await?;
+        self.parts.push(PartState {
+            part_number,
+            e_tag: resp.e_tag.ok_or("パートの ETag が返されていません")?,
+            checksum: checksum.map(|(_, value)| value),
+        });
+        Ok(())
+    }
+
+    /// 残りのデータを送ってアップロードを完了する
+    /// ※ パートを 1 つも送っていない場合は、通常の PutObject で送る
+    pub(super) async fn finish(
+        &mut self,
+        client: &Client,
+        rest: Vec<u8>,
+    ) -> Result<PutResult, BoxError> {
+        let Some(upload_id) = self.upload_id.clone() else {
+            return put_bytes_async(client, &self.bucket, &self.key, rest, &self.options).await;
+        };
+        if !rest.is_empty() {
+            self.send_part(client, rest).await?;
+        }
+        let parts = std::mem::take(&mut self.parts);
+        let result = complete_upload(
+            client,
+            &self.bucket,
+            &self.key,
+            &upload_id,
+            parts,
+            &self.options,
+        )
+        .await?;
+        self.upload_id = None;
+        if let Some(progress) = &self.options.progress {
+            progress.file_finished(&self.key);
+        }
+        Ok(result)
+    }
+
+    /// 開始したアップロードを中止する
+    pub(super) async fn abort(&mut self, client: &Client) {
+        if let Some(upload_id) = self.upload_id.take() {
+            abort_upload(client, &self.bucket, &self.key, &upload_id).await;
+        }
+    }
+}
+
+/// CompleteMultipartUpload を実行する
+/// ※ PutOptions.checksum を指定した場合は、S3 が記録した複合チェックサムをパートのチェックサムから検証する
+pub(super) async fn complete_upload(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    mut parts: Vec<PartState>,
+    options: &PutOptions,
+) -> Result<PutResult, BoxError> {
+    parts.sort_by_key(|p| p.part_number);
+    let expected = match options.checksum {
+        Some(algorithm) => {
+            let part_checksums = parts
+                .iter()
+                .map(|p| p.checksum.clone())
+                .collect::<Option<Vec<_>>>()
+                .ok_or("チェックサムが記録されていないパートがあります")?;
+            Some((algorithm, composite_from_parts(algorithm, &part_checksums)?))
+        }
+        None => None,
+    };
+    let completed = CompletedMultipartUpload::builder()
+        .set_parts(Some(
+            parts
+                .into_iter()
+                .map(|p| {
+                    let headers = checksum_headers(options.checksum.zip(p.checksum).as_ref());
+                    CompletedPart::builder()
+                        .part_number(p.part_number)
+                        .e_tag(p.e_tag)
+                        .set_checksum_crc32_c(headers.crc32c)
+                        .set_checksum_sha256(headers.sha256)
+                        .build()
+                })
+                .collect(),
+        ))
+        .build();
+
+    let customer = customer_key_headers(options.encryption.as_ref())?;
+    let resp = client
+        .complete_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .multipart_upload(completed)
+        .set_sse_customer_algorithm(customer.algorithm.clone())
+        .set_sse_customer_key(customer.key)
+        .set_sse_customer_key_md5(customer.key_md5)
+        .send()
+        .await?;
+    let stored = expected.as_ref().and_then(|(algorithm, _)| {
+        algorithm.pick(
+            resp.checksum_crc32_c.as_deref(),
+            resp.checksum_sha256.as_deref(),
+        )
+    });
+    if let (Some((algorithm, expected)), Some(stored)) = (&expected, stored) {
+        verify_checksum(key, *algorithm, expected, stored)?;
+    }
+    // CompleteMultipartUpload は SSE-C のアルゴリズムを返さないため、指定した値で補う
+    Ok(PutResult {
+        e_tag: resp.e_tag.as_deref().map(trim_etag),
+        version_id: resp.version_id,
+        encryption: EncryptionInfo::from_headers(
+            resp.server_side_encryption.as_ref(),
+            resp.ssekms_key_id.as_deref(),
+            customer.algorithm.as_deref(),
+        ),
+        checksum: stored.map(str::to_string),
+    })
+}
+
+/// 状態ファイル（JSON形式）を読み込む
+fn load_state(path: &Path) -> Option<UploadState> {
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// 状態ファイル（JSON形式）を書き出す（一時ファイルに書き込んでからリネームする）
+fn save_state(path: &Path, state: &UploadState) -> Result<(), BoxError> {
+    let tmp = temp_path(path)?;
+    fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use aws_sdk_s3::operation::head_object::HeadObjectOutput;
+use aws_sdk_s3::types::{Tag, Tagging};
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use super::encryption::EncryptionInfo;
+use super::{run_sync, to_chrono, trim_etag, BoxError};
+
+/// HeadObject で取得したオブジェクトのメタデータ
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectMetadata {
+    /// サイズ（バイト）
+    pub size: u64,
+    pub content_type: Option<String>,
+    pub content_encoding: Option<String>,
+    pub cache_control: Option<String>,
+    /// ETag（前後のダブルクォートは取り除いた値）
+    pub e_tag: Option<String>,
+    /// バージョン ID（バージョニングが有効なバケットのみ）
+    pub version_id: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
+    /// ユーザー定義メタデータ（x-amz-meta-*）
+    pub metadata: HashMap<String, String>,
+    /// ストレージクラス（S3 が省略する STANDARD も補って返す）
+    pub storage_class: String,
+    /// Glacier からの復元状況（復元リクエストが無い場合は None）
+    pub restore: Option<RestoreStatus>,
+    /// オブジェクトに適用されている暗号化
+    pub encryption: Option<EncryptionInfo>,
+}
+
+/// Glacier からの復元状況
+#[derive(Debug, Clone, PartialEq)]
+pub enum RestoreStatus {
+    /// 復元中
+    InProgress,
+    /// 復元済み（expiry は一時コピーが削除される日時）
+    Restored { expiry: Option<DateTime<Utc>> },
+}
+
+impl From<HeadObjectOutput> for ObjectMetadata {
+    fn from(head: HeadObjectOutput) -> Self {
+        let encryption = EncryptionInfo::from_headers(
+            head.server_side_encryption.as_ref(),
+            head.ssekms_key_id.as_deref(),
+            head.sse_customer_algorithm.as_deref(),
+        );
+        ObjectMetadata {
+            size: head.content_length.unwrap_or(0).max(0) as u64,
+            content_type: head.content_type,
+            content_encoding: head.content_encoding,
+            cache_control: head.cache_control,
+            e_tag: head.e_tag.as_deref().map(trim_etag),
+            version_id: head.version_id,
+            last_modified: head.last_modified.as_ref().and_then(to_chrono),
+            metadata: head.metadata.unwrap_or_default(),
+            storage_class: head
+                .storage_class
+                .map(|c| c.as_str().to_string())
+                .unwrap_or_else(|| "STANDARD".to_string()),
+            restore: head.restore.as_deref().map(parse_restore),
+            encryption,
+        }
+    }
+}
+
+/// オブジェクトのメタデータを取得する（非同期版）
+pub async fn head_object_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> Result<ObjectMetadata, BoxError> {
+    let head = client.head_object().bucket(bucket).key(key).send().await?;
+    Ok(head.into())
+}
+
+/// オブジェクトが存在するかどうかを返す（非同期版）
+/// ※ 404 は false として返し、権限エラーなどそれ以外はエラーとして返す
+pub async fn exists_async(client: &Client, bucket: &str, key: &str) -> Result<bool, BoxError> {
+    match client.head_object().bucket(bucket).key(key).send().await {
+        Ok(_) => Ok(true),
+        Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// オブジェクトのタグを取得する（非同期版）
+pub async fn get_object_tags_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> Result<HashMap<String, String>, BoxError> {
+    let resp = client
+        .get_object_tagging()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await?;
+    Ok(resp
+        .tag_set
+        .into_iter()
+        .map(|tag| (tag.key, tag.value))
+        .collect())
+}
+
+/// オブジェクトのタグを設定する（既存のタグはすべて置き換える）（非同期版）
+pub async fn put_object_tags_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    tags: &HashMap<String, String>,
+) -> Result<(), BoxError> {
+    let tag_set = tags
+        .iter()
+        .map(|(k, v)| Tag::builder().key(k).value(v).build())
+        .collect::<Result<Vec<_>, _>>()?;
+    let tagging = Tagging::builder().set_tag_set(Some(tag_set)).build()?;
+    client
+        .put_object_tagging()
+        .bucket(bucket)
+        .key(key)
+        .tagging(tagging)
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// オブジェクトのタグをすべて削除する（非同期版）
+pub async fn delete_object_tags_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> Result<(), BoxError> {
+    client
+        .delete_object_tagging()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// オブジェクトのメタデータを取得する
+pub fn head_object(bucket: &str, key: &str) -> Result<ObjectMetadata, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { head_object_async(&s3, bucket, key).await })
+}
+
+/// オブジェクトが存在するかどうかを返す
+pub fn exists(bucket: &str, key: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { exists_async(&s3, bucket, key).await })
+}
+
+/// オブジェクトのタグを取得する
+pub fn get_object_tags(
+    bucket: &str,
+    key: &str,
+) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { get_object_tags_async(&s3, bucket, key).await })
+}
+
+/// オブジェクトのタグを設定する（既存のタグはすべて置き換える）
+pub fn put_object_tags(
+    bucket: &str,
+    key: &str,
+    tags: &HashMap<String, String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { put_object_tags_async(&s3, bucket, key, tags).await })
+}
+
+/// オブジェクトのタグをすべて削除する
+pub fn delete_object_tags(bucket: &str, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { delete_object_tags_async(&s3, bucket, key).await })
+}
+
+/// x-amz-restore ヘッダーを解釈する
+/// 例: ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"
+fn parse_restore(value: &str) -> RestoreStatus {
+    if value.contains(r#"ongoing-request="true""#) {
+        return RestoreStatus::InProgress;
+    }
+    let expiry = value
+        .split_once(r#"expiry-date=""#)
+        .and_then(|(_, rest)| rest.split('"').next())
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.with_timezone(&Utc));
+    RestoreStatus::Restored { expiry }
+}
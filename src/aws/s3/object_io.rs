@@ -0,0 +1,284 @@
+use aws_sdk_s3::Client;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use tokio::runtime::Runtime;
+
+use super::bandwidth::throttle;
+use super::compression::EncodingWriter;
+use super::download::GetOptions;
+use super::encryption::{customer_key_headers, CustomerKeyHeaders};
+use super::multipart::StreamingUpload;
+use super::progress::ProgressReporter;
+use super::upload::{compressed_target, PutOptions, PutResult};
+use super::BoxError;
+use crate::aws::config::make_client;
+
+/// S3ObjectReader の既定の先読みサイズ（8 MiB）
+const DEFAULT_READ_AHEAD: usize = 8 * 1024 * 1024;
+
+/// S3ObjectWriter の既定のパートサイズ（16 MiB）
+const DEFAULT_WRITE_PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// S3 のオブジェクトを Read + Seek として読み込むアダプタ
+/// ※ 読み込み位置から先読みサイズ分を Range 指定の GET でまとめて取得する
+/// ※ 開いた時点の ETag を If-Match に指定するため、途中で上書きされた場合はエラーになる
+pub struct S3ObjectReader {
+    rt: Runtime,
+    client: Client,
+    bucket: String,
+    key: String,
+    size: u64,
+    e_tag: Option<String>,
+    customer: CustomerKeyHeaders,
+    progress: Option<ProgressReporter>,
+    /// 現在の読み込み位置
+    pos: u64,
+    /// 先読みしたデータと、その先頭の位置
+    buf: Vec<u8>,
+    buf_start: u64,
+    read_ahead: usize,
+}
+
+impl S3ObjectReader {
+    /// オブジェクトを開く（HeadObject でサイズと ETag を取得する）
+    pub fn open(
+        bucket: &str,
+        key: &str,
+        options: &GetOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::open_with_client(make_client()?, bucket, key, options)
+    }
+
+    /// クライアントを指定してオブジェクトを開く
+    pub fn open_with_client(
+        client: Client,
+        bucket: &str,
+        key: &str,
+        options: &GetOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let rt = Runtime::new()?;
+        let customer = customer_key_headers(options.encryption.as_ref())
+            .map_err(|e| e as Box<dyn std::error::Error>)?;
+        let request = client
+            .head_object()
+            .bucket(bucket)
+            .key(key)
+            .set_sse_customer_algorithm(customer.algorithm.clone())
+            .set_sse_customer_key(customer.key.clone())
+            .set_sse_customer_key_md5(customer.key_md5.clone());
+        let head = rt.block_on(request.send())?;
+        let size = head.content_length.unwrap_or(0).max(0) as u64;
+        if let Some(progress) = &options.progress {
+            progress.file_started(key, size);
+        }
+        Ok(S3ObjectReader {
+            rt,
+            client,
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            size,
+            e_tag: head.e_tag,
+            customer,
+            progress: options.progress.clone(),
+            pos: 0,
+            buf: Vec::new(),
+            buf_start: 0,
+            read_ahead: DEFAULT_READ_AHEAD,
+        })
+    }
+
+    /// 1 回の GET で先読みするサイズ（バイト）を変更する
+    pub fn with_read_ahead(mut self, read_ahead: usize) -> Self {
+        self.read_ahead = read_ahead.max(1);
+        self
+    }
+
+    /// オブジェクトのサイズ（バイト）
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// pos から len バイト（最低でも先読みサイズ分）を取得してバッファを置き換える
+    fn fill(&mut self, len: usize) -> io::Result<()> {
+        let end = (self.pos + len.max(self.read_ahead) as u64).min(self.size);
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .range(format!("bytes={}-{}", self.pos, end - 1))
+            .set_if_match(self.e_tag.clone())
+            .set_sse_customer_algorithm(self.customer.algorithm.clone())
+            .set_sse_customer_key(self.customer.key.clone())
+            .set_sse_customer_key_md5(self.customer.key_md5.clone());
+        let bytes = self
+            .rt
+            .block_on(async {
+                let resp = request.send().await?;
+                let bytes = resp.body.collect().await?.into_bytes();
+                throttle(bytes.len() as u64).await;
+                Ok::<_, BoxError>(bytes)
+            })
+            .map_err(io::Error::other)?;
+        if let Some(progress) = &self.progress {
+            progress.transferred(bytes.len() as u64);
+        }
+        self.buf = bytes.to_vec();
+        self.buf_start = self.pos;
+        Ok(())
+    }
+}
+
+impl Read for S3ObjectReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() || self.pos >= self.size {
+            return Ok(0);
+        }
+        let buf_end = self.buf_start + self.buf.len() as u64;
+        if self.pos < self.buf_start || self.pos >= buf_end {
+            self.fill(out.len())?;
+        }
+        let offset = (self.pos - self.buf_start) as usize;
+        let n = out.len().min(self.buf.len() - offset);
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "オブジェクトの途中でデータが途切れました",
+            ));
+        }
+        out[..n].copy_from_slice(&self.buf[offset..offset + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for S3ObjectReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        // 末尾より後ろへのシークは許可する（読み込むと 0 バイトになる）
+        self.pos = new_pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "先頭より前にはシークできません",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+/// S3 のオブジェクトに Write で書き込むアダプタ
+/// ※ パートサイズ分たまるごとにマルチパートアップロードのパートとして送信する
+/// ※ finish() を呼ぶまでオブジェクトは作成されない（呼ばずに破棄するとアップロードを中止する）
+/// ※ 書き込んだ量がパートサイズ未満の場合は finish() で通常の PutObject を使う
+/// ※ PutOptions.compression を指定した場合は、書き込んだデータを逐次圧縮して送信する
+pub struct S3ObjectWriter {
+    /// Drop で非同期のコンテキストから破棄する場合だけ None になる
+    rt: Option<Runtime>,
+    client: Client,
+    /// 圧縮する場合の圧縮器（圧縮したデータは内部の Vec に溜まる）
+    encoder: Option<EncodingWriter<Vec<u8>>>,
+    buf: Vec<u8>,
+    upload: StreamingUpload,
+}
+
+impl S3ObjectWriter {
+    /// 書き込み先のオブジェクトを指定して生成する
+    pub fn create(
+        bucket: &str,
+        key: &str,
+        options: &PutOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::create_with_client(make_client()?, bucket, key, options)
+    }
+
+    /// クライアントを指定して生成する
+    pub fn create_with_client(
+        client: Client,
+        bucket: &str,
+        key: &str,
+        options: &PutOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (key, options, encoder) = match options.compression {
+            Some(compression) => {
+                let (key, options) = compressed_target(compression, key, None, options);
+                let encoder = EncodingWriter::new(compression, Vec::new())?;
+                (key, options, Some(encoder))
+            }
+            None => (key.to_string(), options.clone(), None),
+        };
+        Ok(S3ObjectWriter {
+            rt: Some(Runtime::new()?),
+            client,
+            encoder,
+            buf: Vec::new(),
+            upload: StreamingUpload::new(bucket, &key, &options, DEFAULT_WRITE_PART_SIZE),
+        })
+    }
+
+    /// 最初のパートサイズ（バイト）を変更する（MIN_PART_SIZE 未満は MIN_PART_SIZE になる）
+    /// ※ パート数の上限を超えないよう、1000 パートごとにパートサイズを倍にする
+    pub fn with_part_size(mut self, part_size: usize) -> Self {
+        self.upload.set_part_size(part_size as u64);
+        self
+    }
+
+    /// 残りのデータを送信してアップロードを完了する
+    pub fn finish(mut self) -> Result<PutResult, Box<dyn std::error::Error>> {
+        if let Some(encoder) = self.encoder.take() {
+            self.buf.append(&mut encoder.finish()?);
+        }
+        let buf = std::mem::take(&mut self.buf);
+        let rt = self.rt.as_ref().ok_or("ランタイムが終了しています")?;
+        rt.block_on(self.upload.finish(&self.client, buf))
+            .map_err(|e| e as Box<dyn std::error::Error>)
+    }
+}
+
+impl Write for S3ObjectWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            Some(encoder) => {
+                encoder.write_all(data)?;
+                self.buf.append(encoder.get_mut());
+            }
+            None => self.buf.extend_from_slice(data),
+        }
+        let rt = self
+            .rt
+            .as_ref()
+            .ok_or_else(|| io::Error::other("ランタイムが終了しています"))?;
+        while self.buf.len() >= self.upload.part_size() {
+            let rest = self.buf.split_off(self.upload.part_size());
+            let part = std::mem::replace(&mut self.buf, rest);
+            rt.block_on(self.upload.send_part(&self.client, part))
+                .map_err(io::Error::other)?;
+        }
+        Ok(data.len())
+    }
+
+    /// ※ パートサイズに満たないデータは finish() まで送信しない
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for S3ObjectWriter {
+    fn drop(&mut self) {
+        let Some(rt) = self.rt.take() else {
+            return;
+        };
+        match tokio::runtime::Handle::try_current() {
+            // 非同期のコンテキストでは block_on もランタイムの破棄もできないため、
+            // 中止は呼び出し元のランタイムで行い、自身のランタイムは待たずに終了する
+            Ok(handle) => {
+                let (client, mut upload) = (self.client.clone(), self.upload.clone());
+                handle.spawn(async move { upload.abort(&client).await });
+                rt.shutdown_background();
+            }
+            Err(_) => rt.block_on(self.upload.abort(&self.client)),
+        }
+    }
+}
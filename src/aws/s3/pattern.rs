@@ -0,0 +1,177 @@
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+use super::uri::S3Uri;
+use super::BoxError;
+
+/// オブジェクトキーのパターン（グロブまたは正規表現）
+/// ※ ListObjectsV2 にはパターン先頭の固定部分（prefix）だけを渡し、残りは取得したキーに対して照合する
+#[derive(Debug, Clone)]
+pub struct KeyPattern {
+    source: String,
+    prefix: String,
+    matcher: Matcher,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// prefix で始まるキーすべて
+    Prefix,
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+impl KeyPattern {
+    /// グロブからパターンを作る
+    /// ※ キー全体に対して照合する。* と ? は / を越えず、** は任意の階層に一致する
+    /// ※ [abc]・{a,b} と、\ によるエスケープも使える
+    pub fn glob(pattern: &str) -> Result<Self, BoxError> {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .backslash_escape(true)
+            .build()?;
+        Ok(KeyPattern {
+            source: pattern.to_string(),
+            prefix: glob_prefix(pattern),
+            matcher: Matcher::Glob(glob.compile_matcher()),
+        })
+    }
+
+    /// 正規表現からパターンを作る
+    /// ※ キーの一部に一致すればよい（全体に一致させるには ^...$ で囲む）
+    /// ※ ^ で始まる場合だけ、続く固定部分を一覧のプレフィックスに使う（それ以外はバケット全体を一覧する）
+    pub fn regex(pattern: &str) -> Result<Self, BoxError> {
+        Ok(KeyPattern {
+            source: pattern.to_string(),
+            prefix: regex_prefix(pattern),
+            matcher: Matcher::Regex(Regex::new(pattern)?),
+        })
+    }
+
+    /// s3://bucket/logs/2026-*/**/*.json 形式の URI をバケット名とグロブに分ける
+    pub fn from_uri(uri: &str) -> Result<(String, Self), BoxError> {
+        let uri = S3Uri::from_str(uri)?;
+        Ok((uri.bucket().to_string(), KeyPattern::glob(uri.key())?))
+    }
+
+    /// プレフィックス配下のすべてのキーに一致するパターン
+    pub(super) fn under(prefix: &str) -> Self {
+        KeyPattern {
+            source: prefix.to_string(),
+            prefix: prefix.to_string(),
+            matcher: Matcher::Prefix,
+        }
+    }
+
+    /// ListObjectsV2 に渡すプレフィックス（パターン先頭の固定部分）
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// コピー先・ダウンロード先でのキーの起点
+    /// ※ prefix の最後の / までの部分（"logs/2026-*/a.json" なら "logs/"）。under の場合は prefix そのもの
+    pub fn base(&self) -> &str {
+        match self.matcher {
+            Matcher::Prefix => &self.prefix,
+            _ => match self.prefix.rfind('/') {
+                Some(pos) => &self.prefix[..=pos],
+                None => "",
+            },
+        }
+    }
+
+    /// キーがパターンに一致するかどうか
+    pub fn is_match(&self, key: &str) -> bool {
+        match &self.matcher {
+            Matcher::Prefix => key.starts_with(&self.prefix),
+            Matcher::Glob(glob) => glob.is_match(key),
+            Matcher::Regex(regex) => regex.is_match(key),
+        }
+    }
+
+    /// 元のパターン
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl fmt::Display for KeyPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// グロブ先頭の固定部分
+fn glob_prefix(pattern: &str) -> String {
+    let mut prefix = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '?' | '[' | '{' => break,
+            '\\' => match chars.next() {
+                Some(escaped) => prefix.push(escaped),
+                None => break,
+            },
+            _ => prefix.push(c),
+        }
+    }
+    prefix
+}
+
+/// ^ で始まる正規表現の先頭の固定部分
+/// ※ 直後に * ? {n,m} が付く文字は省略されうるため含めない。最上位に | がある場合は空にする
+fn regex_prefix(pattern: &str) -> String {
+    let Some(rest) = pattern.strip_prefix('^') else {
+        return String::new();
+    };
+    if has_top_level_alternation(rest) {
+        return String::new();
+    }
+
+    let chars: Vec<char> = rest.chars().collect();
+    let mut prefix = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (literal, len) = match chars[i] {
+            '\\' => match chars.get(i + 1) {
+                Some(&c) if c.is_ascii_punctuation() => (c, 2),
+                _ => break,
+            },
+            '.' | '^' | '$' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|' => break,
+            c => (c, 1),
+        };
+        match chars.get(i + len) {
+            Some('*') | Some('?') | Some('{') => break,
+            Some('+') => {
+                prefix.push(literal);
+                break;
+            }
+            _ => prefix.push(literal),
+        }
+        i += len;
+    }
+    prefix
+}
+
+/// グループや文字クラスの外に | があるかどうか
+fn has_top_level_alternation(pattern: &str) -> bool {
+    let mut depth = 0usize;
+    let mut in_class = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '(' if !in_class => depth += 1,
+            ')' if !in_class => depth = depth.saturating_sub(1),
+            '|' if !in_class && depth == 0 => return true,
+            _ => {}
+        }
+    }
+    false
+}
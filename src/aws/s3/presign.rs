@@ -0,0 +1,116 @@
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::Client;
+use std::time::{Duration, SystemTime};
+
+use super::{run_sync, BoxError};
+use crate::aws::config::session_expiration;
+
+/// 署名付き URL のオプション
+#[derive(Debug, Clone)]
+pub struct PresignOptions {
+    /// 有効期間（最大 7 日）
+    pub expires_in: Duration,
+    /// GET: レスポンスの Content-Type を上書きする／PUT: アップロード時に必須とする Content-Type
+    pub content_type: Option<String>,
+    /// GET: レスポンスの Content-Disposition を上書きする／PUT: オブジェクトに設定する値
+    pub content_disposition: Option<String>,
+}
+
+impl Default for PresignOptions {
+    fn default() -> Self {
+        PresignOptions {
+            expires_in: Duration::from_secs(3600),
+            content_type: None,
+            content_disposition: None,
+        }
+    }
+}
+
+/// 生成した署名付き URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresignedUrl {
+    pub url: String,
+    /// 有効期間
+    pub expires_in: Duration,
+    /// 有効期間が AssumeRole のセッションの残り時間より長い場合の警告
+    /// ※ セッションが切れた時点で URL も使えなくなる。表示するかどうかは呼び出し側で決める
+    pub warning: Option<String>,
+}
+
+/// ダウンロード用の署名付き URL を生成する（非同期版）
+pub async fn presign_get_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    options: &PresignOptions,
+) -> Result<PresignedUrl, BoxError> {
+    let request = client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .set_response_content_type(options.content_type.clone())
+        .set_response_content_disposition(options.content_disposition.clone())
+        .presigned(PresigningConfig::expires_in(options.expires_in)?)
+        .await?;
+    Ok(PresignedUrl {
+        url: request.uri().to_string(),
+        expires_in: options.expires_in,
+        warning: session_warning(options.expires_in),
+    })
+}
+
+/// アップロード用の署名付き URL を生成する（非同期版）
+/// ※ content_type を指定した場合、アップロード側も同じ Content-Type ヘッダーを送る必要がある
+pub async fn presign_put_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    options: &PresignOptions,
+) -> Result<PresignedUrl, BoxError> {
+    let request = client
+        .put_object()
+        .bucket(bucket)
+        .key(key)
+        .set_content_type(options.content_type.clone())
+        .set_content_disposition(options.content_disposition.clone())
+        .presigned(PresigningConfig::expires_in(options.expires_in)?)
+        .await?;
+    Ok(PresignedUrl {
+        url: request.uri().to_string(),
+        expires_in: options.expires_in,
+        warning: session_warning(options.expires_in),
+    })
+}
+
+/// ダウンロード用の署名付き URL を生成する
+pub fn presign_get(
+    bucket: &str,
+    key: &str,
+    options: &PresignOptions,
+) -> Result<PresignedUrl, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { presign_get_async(&s3, bucket, key, options).await })
+}
+
+/// アップロード用の署名付き URL を生成する
+pub fn presign_put(
+    bucket: &str,
+    key: &str,
+    options: &PresignOptions,
+) -> Result<PresignedUrl, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { presign_put_async(&s3, bucket, key, options).await })
+}
+
+/// 有効期間が AssumeRole のセッションより長い場合の警告文を返す
+/// ※ セッションが切れた時点で URL も使えなくなるため
+fn session_warning(expires_in: Duration) -> Option<String> {
+    let remaining = session_expiration()?
+        .duration_since(SystemTime::now())
+        .unwrap_or_default();
+    (expires_in > remaining).then(|| {
+        format!(
+            "署名付き URL の有効期間（{} 秒）がセッションの残り時間（{} 秒）を超えています。セッションの期限切れとともに URL も無効になります",
+            expires_in.as_secs(),
+            remaining.as_secs()
+        )
+    })
+}
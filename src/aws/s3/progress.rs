@@ -0,0 +1,258 @@
+use std::fmt;
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 転送全体の進捗（ProgressReporter を共有するすべての転送の合計）
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferProgress {
+    /// 転送済みのバイト数
+    pub bytes_done: u64,
+    /// 開始したファイルのサイズの合計
+    pub bytes_total: u64,
+    /// 完了したファイル数
+    pub files_done: usize,
+    /// 開始したファイル数
+    pub files_total: usize,
+    /// 最初の転送を開始してからの経過時間
+    pub elapsed: Duration,
+}
+
+impl TransferProgress {
+    /// 平均転送速度（バイト/秒）
+    pub fn rate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes_done as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// 平均転送速度から求めた残り時間（速度が 0 の場合は None）
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.rate();
+        if rate <= 0.0 {
+            return None;
+        }
+        let remaining = self.bytes_total.saturating_sub(self.bytes_done);
+        Some(Duration::from_secs_f64(remaining as f64 / rate))
+    }
+
+    /// 進捗率（0.0〜1.0、合計が 0 の場合は None）
+    pub fn fraction(&self) -> Option<f64> {
+        (self.bytes_total > 0).then(|| (self.bytes_done as f64 / self.bytes_total as f64).min(1.0))
+    }
+}
+
+/// 進捗の通知内容
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressKind {
+    /// ファイル（オブジェクト）の転送を開始した
+    FileStarted { key: String, size: u64 },
+    /// bytes バイトを転送した
+    Transferred { bytes: u64 },
+    /// ファイル（オブジェクト）の転送が完了した
+    FileFinished { key: String },
+}
+
+/// 進捗の通知（通知時点の全体の進捗を含む）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressEvent {
+    pub kind: ProgressKind,
+    pub progress: TransferProgress,
+}
+
+type Callback = Box<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+/// 転送の進捗を通知する
+/// ※ PutOptions や GetOptions などの progress に指定する。clone したものは同じ集計を共有する
+/// ※ コールバックは転送中のスレッドから呼ばれるため、重い処理はチャネルの受信側で行う
+#[derive(Clone)]
+pub struct ProgressReporter {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    callback: Callback,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    started: Option<Instant>,
+    bytes_done: u64,
+    bytes_total: u64,
+    files_done: usize,
+    files_total: usize,
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ProgressReporter")
+            .field(&self.snapshot())
+            .finish()
+    }
+}
+
+impl ProgressReporter {
+    /// 進捗のたびに callback を呼ぶ
+    pub fn new(callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        ProgressReporter {
+            inner: Arc::new(Inner {
+                callback: Box::new(callback),
+                state: Mutex::new(State::default()),
+            }),
+        }
+    }
+
+    /// 進捗をチャネルに送る（受信側が破棄された後の通知は捨てる）
+    pub fn channel() -> (Self, Receiver<ProgressEvent>) {
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let reporter = ProgressReporter::new(move |event| {
+            if let Ok(tx) = tx.lock() {
+                let _ = tx.send(event.clone());
+            }
+        });
+        (reporter, rx)
+    }
+
+    /// 標準エラー出力に進捗バーを表示する
+    /// ※ 描画は 0.2 秒に 1 回まで。開始したファイルがすべて完了した時点で改行する
+    pub fn progress_bar() -> Self {
+        let last_draw = Mutex::new(None::<Instant>);
+        ProgressReporter::new(move |event| {
+            let finished = matches!(event.kind, ProgressKind::FileFinished { .. })
+                && event.progress.files_done == event.progress.files_total;
+            let Ok(mut last_draw) = last_draw.lock() else {
+                return;
+            };
+            let now = Instant::now();
+            if !finished && last_draw.is_some_and(|t| now - t < Duration::from_millis(200)) {
+                return;
+            }
+            *last_draw = Some(now);
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r{}", render_bar(&event.progress));
+            if finished {
+                let _ = writeln!(stderr);
+            }
+            let _ = stderr.flush();
+        })
+    }
+
+    /// 現在の進捗
+    pub fn snapshot(&self) -> TransferProgress {
+        match self.inner.state.lock() {
+            Ok(state) => state.progress(),
+            Err(_) => TransferProgress {
+                bytes_done: 0,
+                bytes_total: 0,
+                files_done: 0,
+                files_total: 0,
+                elapsed: Duration::ZERO,
+            },
+        }
+    }
+
+    pub(super) fn file_started(&self, key: &str, size: u64) {
+        self.update(
+            |state| {
+                state.files_total += 1;
+                state.bytes_total += size;
+            },
+            ProgressKind::FileStarted {
+                key: key.to_string(),
+                size,
+            },
+        );
+    }
+
+    pub(super) fn transferred(&self, bytes: u64) {
+        self.update(
+            |state| state.bytes_done += bytes,
+            ProgressKind::Transferred { bytes },
+        );
+    }
+
+    pub(super) fn file_finished(&self, key: &str) {
+        self.update(
+            |state| state.files_done += 1,
+            ProgressKind::FileFinished {
+                key: key.to_string(),
+            },
+        );
+    }
+
+    /// サイズが事前にわからない転送で、合計に bytes を加える
+    pub(super) fn add_total(&self, bytes: u64) {
+        if let Ok(mut state) = self.inner.state.lock() {
+            state.bytes_total += bytes;
+        }
+    }
+
+    /// 集計を更新してからコールバックを呼ぶ（コールバック中はロックを保持しない）
+    fn update(&self, apply: impl FnOnce(&mut State), kind: ProgressKind) {
+        let progress = match self.inner.state.lock() {
+            Ok(mut state) => {
+                state.started.get_or_insert_with(Instant::now);
+                apply(&mut state);
+                state.progress()
+            }
+            Err(_) => return,
+        };
+        (self.inner.callback)(&ProgressEvent { kind, progress });
+    }
+}
+
+impl State {
+    fn progress(&self) -> TransferProgress {
+        TransferProgress {
+            bytes_done: self.bytes_done,
+            bytes_total: self.bytes_total,
+            files_done: self.files_done,
+            files_total: self.files_total,
+            elapsed: self.started.map(|t| t.elapsed()).unwrap_or_default(),
+        }
+    }
+}
+
+/// 進捗バーの 1 行分の文字列
+fn render_bar(progress: &TransferProgress) -> String {
+    const WIDTH: usize = 30;
+    let fraction = progress.fraction().unwrap_or(0.0);
+    let filled = (fraction * WIDTH as f64) as usize;
+    let eta = progress
+        .eta()
+        .map(|eta| {
+            let secs = eta.as_secs();
+            format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+        })
+        .unwrap_or_else(|| "--:--:--".to_string());
+    format!(
+        "[{}{}] {:5.1}% {} / {} {}/s 残り {} ({}/{})",
+        "#".repeat(filled),
+        "-".repeat(WIDTH - filled),
+        fraction * 100.0,
+        format_bytes(progress.bytes_done as f64),
+        format_bytes(progress.bytes_total as f64),
+        format_bytes(progress.rate()),
+        eta,
+        progress.files_done,
+        progress.files_total,
+    )
+}
+
+/// バイト数を KiB / MiB / GiB 単位の文字列にする
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
@@ -0,0 +1,374 @@
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::get_object::GetObjectError;
+use aws_sdk_s3::primitives::ByteStreamError;
+use aws_sdk_s3::types::ChecksumMode;
+use aws_sdk_s3::Client;
+use md5::{Digest, Md5};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use super::bandwidth::throttle;
+use super::checksum::{expected_checksum, file_checksum, ChecksumAlgorithm};
+use super::download::{is_etag_opaque, is_md5_etag, temp_path, DownloadInfo};
+use super::encryption::{customer_key_headers, CustomerKeyHeaders, Encryption, EncryptionInfo};
+use super::error::S3Error;
+use super::progress::ProgressReporter;
+use super::{run_concurrently, run_sync, to_chrono, trim_etag, BoxError};
+
+/// 分割ダウンロードのオプション
+#[derive(Debug, Clone)]
+pub struct RangedOptions {
+    /// 1 リクエストで取得する範囲の大きさ（バイト）
+    pub part_size: u64,
+    /// 同時に取得する範囲の数
+    pub concurrency: usize,
+    /// 範囲ごとの再試行回数
+    pub max_retries: u32,
+    /// オブジェクトが SSE-C で暗号化されている場合の鍵
+    pub encryption: Option<Encryption>,
+    /// 保存したファイルと照合するチェックサム（GetOptions.checksum と同じ）
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// 進捗の通知先
+    pub progress: Option<ProgressReporter>,
+}
+
+impl Default for RangedOptions {
+    fn default() -> Self {
+        RangedOptions {
+            part_size: 16 * 1024 * 1024,
+            concurrency: 8,
+            max_retries: 3,
+            encryption: None,
+            checksum: None,
+            progress: None,
+        }
+    }
+}
+
+/// オブジェクトを範囲ごとに並行取得してファイルに保存する（非同期版）
+/// ※ 事前に確保したファイルの該当位置に書き込み、最後に ETag と照合する
+/// ※ 取得中にオブジェクトが更新された場合は If-Match により失敗する
+pub async fn download_file_ranged_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &RangedOptions,
+) -> Result<DownloadInfo, BoxError> {
+    let customer = customer_key_headers(options.encryption.as_ref())?;
+    let head = client
+        .head_object()
+        .bucket(bucket)
+        .key(key)
+        .set_sse_customer_algorithm(customer.algorithm.clone())
+        .set_sse_customer_key(customer.key.clone())
+        .set_sse_customer_key_md5(customer.key_md5.clone())
+        .set_checksum_mode(options.checksum.map(|_| ChecksumMode::Enabled))
+        .send()
+        .await?;
+    let checksum = match options.checksum {
+        Some(algorithm) => {
+            let value = algorithm.pick(
+                head.checksum_crc32_c.as_deref(),
+                head.checksum_sha256.as_deref(),
+            );
+            expected_checksum(client, bucket, key, None, &customer, algorithm, value).await?
+        }
+        None => None,
+    };
+    let size = head.content_length.unwrap_or(0).max(0) as u64;
+    let raw_e_tag = head.e_tag.clone().ok_or("ETag が返されていません")?;
+    let e_tag = trim_etag(&raw_e_tag);
+    let last_modified = head.last_modified.as_ref().and_then(to_chrono);
+    let opaque = is_etag_opaque(
+        head.server_side_encryption.as_ref(),
+        head.sse_customer_algorithm.as_deref(),
+    );
+    let encryption = EncryptionInfo::from_headers(
+        head.server_side_encryption.as_ref(),
+        head.ssekms_key_id.as_deref(),
+        head.sse_customer_algorithm.as_deref(),
+    );
+
+    if let Some(progress) = &options.progress {
+        progress.file_started(key, size);
+    }
+
+    let tmp = temp_path(path)?;
+    let result: Result<DownloadInfo, BoxError> = async {
+        File::create(&tmp)?.set_len(size)?;
+        let target = RangeTarget {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            if_match: raw_e_tag.clone(),
+            path: tmp.clone(),
+            customer: customer.clone(),
+            progress: options.progress.clone(),
+        };
+        download_ranges(client, &target, size, options).await?;
+        if !opaque {
+            verify_file_etag(client, bucket, key, &tmp, &e_tag).await?;
+        }
+        if let Some(expected) = &checksum {
+            let actual = file_checksum(&tmp, expected.algorithm, expected.part_size)?;
+            expected.verify(key, &actual)?;
+        }
+
+        let file = OpenOptions::new().write(true).open(&tmp)?;
+        file.sync_all()?;
+        if let Some(last_modified) = last_modified {
+            file.set_modified(SystemTime::from(last_modified))?;
+        }
+        drop(file);
+        fs::rename(&tmp, path)?;
+        if let Some(progress) = &options.progress {
+            progress.file_finished(key);
+        }
+        Ok(DownloadInfo {
+            size,
+            e_tag: Some(e_tag.clone()),
+            last_modified,
+            encryption: encryption.clone(),
+            checksum: checksum.as_ref().map(|c| c.value.clone()),
+            compression: None,
+        })
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// オブジェクトを範囲ごとに並行取得してファイルに保存する
+pub fn download_file_ranged(
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &RangedOptions,
+) -> Result<DownloadInfo, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { download_file_ranged_async(&s3, bucket, key, path, options).await })
+}
+
+/// すべての範囲を並行して取得する
+async fn download_ranges(
+    client: &Client,
+    target: &RangeTarget,
+    size: u64,
+    options: &RangedOptions,
+) -> Result<(), BoxError> {
+    let part_size = options.part_size.max(1);
+    let tasks = (0..size).step_by(part_size as usize).map(|start| {
+        let range = ByteRange {
+            start,
+            end: (start + part_size).min(size) - 1,
+        };
+        fetch_range_with_retry(client.clone(), target.clone(), range, options.max_retries)
+    });
+    run_concurrently(tasks, options.concurrency, |_| Ok(())).await
+}
+
+/// 取得対象のオブジェクトと書き込み先
+#[derive(Clone)]
+struct RangeTarget {
+    bucket: String,
+    key: String,
+    if_match: String,
+    path: PathBuf,
+    customer: CustomerKeyHeaders,
+    progress: Option<ProgressReporter>,
+}
+
+/// 取得するバイト範囲（end を含む）
+#[derive(Debug, Clone, Copy)]
+struct ByteRange {
+    start: u64,
+    end: u64,
+}
+
+/// 1 つの範囲を取得する。一時的な失敗の場合は間隔を空けて再試行する
+async fn fetch_range_with_retry(
+    client: Client,
+    target: RangeTarget,
+    range: ByteRange,
+    max_retries: u32,
+) -> Result<(), BoxError> {
+    let mut attempt = 0;
+    loop {
+        match fetch_range(&client, &target, range).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < max_retries && is_transient(&e) => {
+                attempt += 1;
+                tokio::time::sleep(Duration::from_millis(500 * 2u64.pow(attempt))).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// 再試行すれば成功する見込みがある失敗かどうか
+/// ※ タイムアウト・接続の失敗・5xx・スロットリング・受信途中の切断は再試行する
+/// ※ 412（取得中にオブジェクトが更新された）などの 4xx やファイルへの書き込みの失敗は再試行しない
+fn is_transient(e: &BoxError) -> bool {
+    if let Some(e) = e.downcast_ref::<SdkError<GetObjectError, HttpResponse>>() {
+        return match e {
+            SdkError::TimeoutError(_)
+            | SdkError::DispatchFailure(_)
+            | SdkError::ResponseError(_) => true,
+            SdkError::ServiceError(service) => {
+                let status = service.raw().status().as_u16();
+                status >= 500
+                    || status == 429
+                    || matches!(
+                        e.code(),
+                        Some("SlowDown" | "Throttling" | "ThrottlingException" | "RequestTimeout")
+                    )
+            }
+            _ => false,
+        };
+    }
+    e.is::<ByteStreamError>() || matches!(e.downcast_ref(), Some(S3Error::SizeMismatch { .. }))
+}
+
+/// 1 つの範囲を取得してファイルの該当位置に書き込む
+async fn fetch_range(
+    client: &Client,
+    target: &RangeTarget,
+    range: ByteRange,
+) -> Result<(), BoxError> {
+    let resp = client
+        .get_object()
+        .bucket(&target.bucket)
+        .key(&target.key)
+        .range(format!("bytes={}-{}", range.start, range.end))
+        .if_match(&target.if_match)
+        .set_sse_customer_algorithm(target.customer.algorithm.clone())
+        .set_sse_customer_key(target.customer.key.clone())
+        .set_sse_customer_key_md5(target.customer.key_md5.clone())
+        .send()
+        .await?;
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(&target.path)
+        .await?;
+    file.seek(SeekFrom::Start(range.start)).await?;
+
+    let mut written = 0u64;
+    let mut body = resp.body;
+    while let Some(chunk) = body.try_next().await? {
+        file.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+        throttle(chunk.len() as u64).await;
+    }
+    file.flush().await?;
+
+    let expected = range.end - range.start + 1;
+    if written != expected {
+        return Err(S3Error::SizeMismatch {
+            key: target.key.clone(),
+            expected,
+            actual: written,
+        }
+        .into());
+    }
+    // 再試行で二重に数えないよう、範囲全体を取得できた時点で通知する
+    if let Some(progress) = &target.progress {
+        progress.transferred(written);
+    }
+    Ok(())
+}
+
+/// 保存したファイルが ETag と一致するか検証する
+/// ※ マルチパートでアップロードされたオブジェクトは、1 パート目の大きさから ETag を再計算する
+async fn verify_file_etag(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    e_tag: &str,
+) -> Result<(), BoxError> {
+    let actual = if is_md5_etag(e_tag) {
+        file_etag(path, None)?
+    } else if let Some((_, parts)) = e_tag.split_once('-') {
+        let parts: u64 = parts.parse()?;
+        let first_part = client
+            .head_object()
+            .bucket(bucket)
+            .key(key)
+            .part_number(1)
+            .send()
+            .await?;
+        let part_size = first_part.content_length.unwrap_or(0).max(0) as u64;
+        if part_size == 0 {
+            return Ok(());
+        }
+        let actual = file_etag(path, Some(part_size))?;
+        // パート数が一致しない場合（パートサイズが不揃い）は ETag から検証できない
+        if !actual.ends_with(&format!("-{}", parts)) {
+            return Ok(());
+        }
+        actual
+    } else {
+        return Ok(());
+    };
+
+    if actual != e_tag {
+        return Err(S3Error::ETagMismatch {
+            key: key.to_string(),
+            expected: e_tag.to_string(),
+            actual,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// ファイルの ETag を計算する
+/// ※ part_size を指定した場合は、マルチパートの ETag（各パートの MD5 を連結した MD5 + "-N"）
+pub(super) fn file_etag(path: &Path, part_size: Option<u64>) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut whole = Md5::new();
+    let mut part = Md5::new();
+    let mut part_digests = Vec::new();
+    let mut in_part = 0u64;
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let mut chunk = &buf[..n];
+        match part_size {
+            None => whole.update(chunk),
+            Some(part_size) => {
+                while !chunk.is_empty() {
+                    let take = ((part_size - in_part) as usize).min(chunk.len());
+                    part.update(&chunk[..take]);
+                    in_part += take as u64;
+                    chunk = &chunk[take..];
+                    if in_part == part_size {
+                        part_digests.extend_from_slice(&part.finalize_reset());
+                        in_part = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    match part_size {
+        None => Ok(format!("{:x}", whole.finalize())),
+        Some(_) => {
+            if in_part > 0 {
+                part_digests.extend_from_slice(&part.finalize());
+            }
+            let count = part_digests.len() / 16;
+            Ok(format!("{:x}-{}", Md5::digest(&part_digests), count))
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use md5::{Digest, Md5};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tokio::runtime::Runtime;
+
+use super::bucket::client_for_bucket;
+use super::copy::{copy_object_async, CopyOptions};
+use super::delete::{delete_object_async, DeleteGuard};
+use super::download::{get_object_bytes_async, GetOptions};
+use super::list::{list_stream, ObjectInfo};
+use super::upload::{put_bytes_async, PutOptions};
+use super::uri::S3Uri;
+use super::{to_chrono, trim_etag, BoxError};
+use crate::aws::config::make_client;
+
+/// オブジェクトストレージの共通インターフェース
+/// ※ キーはストアのルート（S3 ならプレフィックス、ローカルならディレクトリ）からの相対キー
+/// ※ 本番は S3Store、結合テストは LocalStore、単体テストは MemoryStore を使う想定
+pub trait ObjectStore {
+    /// オブジェクトの内容を取得する（存在しない場合はエラー）
+    fn get(&self, key: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+
+    /// オブジェクトを作成する（既に存在する場合は上書きする）
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// プレフィックス配下のオブジェクトをキーの昇順で返す
+    fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, Box<dyn std::error::Error>>;
+
+    /// オブジェクトを削除する（存在しない場合も成功とする）
+    fn delete(&self, key: &str) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// オブジェクトの情報を返す（存在しない場合は None）
+    fn head(&self, key: &str) -> Result<Option<ObjectInfo>, Box<dyn std::error::Error>>;
+
+    /// オブジェクトをコピーする
+    fn copy(&self, src_key: &str, dst_key: &str) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// S3 をバックエンドとするストア
+pub struct S3Store {
+    rt: Runtime,
+    client: Client,
+    bucket: String,
+    /// ルートのプレフィックス（空、または "/" で終わる）
+    prefix: String,
+    /// 保護対象のバケットで削除する場合の確認
+    guard: DeleteGuard,
+}
+
+impl S3Store {
+    /// s3://bucket/prefix/ をルートとするストアを生成する
+    pub fn new(uri: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new_with_client(make_client()?, uri)
+    }
+
+    /// クライアントを指定してストアを生成する
+    pub fn new_with_client(client: Client, uri: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let uri = S3Uri::from_str(uri)?.to_dir();
+        let rt = Runtime::new()?;
+        let client = rt
+            .block_on(client_for_bucket(&client, uri.bucket()))
+            .map_err(|e| e as Box<dyn std::error::Error>)?;
+        Ok(S3Store {
+            rt,
+            client,
+            bucket: uri.bucket().to_string(),
+            prefix: uri.key().to_string(),
+            guard: DeleteGuard::default(),
+        })
+    }
+
+    /// 保護対象のバケットでも削除できるように、確認としてバケット名を指定する
+    pub fn with_confirm(mut self, bucket: &str) -> Self {
+        self.guard.confirm = Some(bucket.to_string());
+        self
+    }
+
+    /// 保護対象のバケットを、PROTECTED_BUCKETS 環境変数の代わりに指定する
+    pub fn with_protected(mut self, buckets: &[&str]) -> Self {
+        self.guard.protected = Some(buckets.iter().map(|b| b.to_string()).collect());
+        self
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    fn block_on<T>(
+        &self,
+        future: impl std::future::Future<Output = Result<T, BoxError>>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        self.rt
+            .block_on(future)
+            .map_err(|e| e as Box<dyn std::error::Error>)
+    }
+}
+
+impl ObjectStore for S3Store {
+    fn get(&self, key: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        self.block_on(get_object_bytes_async(
+            &self.client,
+            &self.bucket,
+            &self.full_key(key),
+            &GetOptions::default(),
+        ))
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.block_on(put_bytes_async(
+            &self.client,
+            &self.bucket,
+            &self.full_key(key),
+            data.to_vec(),
+            &PutOptions::default(),
+        ))?;
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, Box<dyn std::error::Error>> {
+        self.block_on(async {
+            let mut objects = Vec::new();
+            let mut stream = list_stream(&self.client, &self.bucket, &self.full_key(prefix), None);
+            while let Some(object) = stream.next_object().await {
+                let mut object = object?;
+                object.key = object.key[self.prefix.len()..].to_string();
+                objects.push(object);
+            }
+            Ok(objects)
+        })
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.block_on(delete_object_async(
+            &self.client,
+            &self.bucket,
+            &self.full_key(key),
+            &self.guard,
+        ))
+    }
+
+    fn head(&self, key: &str) -> Result<Option<ObjectInfo>, Box<dyn std::error::Error>> {
+        let request = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(self.full_key(key));
+        match self.rt.block_on(request.send()) {
+            Ok(head) => Ok(Some(ObjectInfo {
+                key: key.to_string(),
+                size: head.content_length.unwrap_or(0).max(0) as u64,
+                e_tag: head.e_tag.as_deref().map(trim_etag),
+                last_modified: head.last_modified.as_ref().and_then(to_chrono),
+                storage_class: head.storage_class.map(|c| c.as_str().to_string()),
+            })),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn copy(&self, src_key: &str, dst_key: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.block_on(copy_object_async(
+            &self.client,
+            &self.bucket,
+            &self.full_key(src_key),
+            &self.bucket,
+            &self.full_key(dst_key),
+            &CopyOptions::default(),
+        ))?;
+        Ok(())
+    }
+}
+
+/// ローカルディレクトリをバックエンドとするストア
+/// ※ キーの "/" をディレクトリの区切りとして扱う
+/// ※ ETag は S3 の単一 PUT と同じく内容の MD5 とする
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    /// root をルートとするストアを生成する（ディレクトリは最初の put で作成する）
+    pub fn new(root: &Path) -> Self {
+        LocalStore {
+            root: root.to_path_buf(),
+        }
+    }
+
+    /// キーに対応するパスを返す（ルートの外を指すキーはエラー）
+    fn path_for(&self, key: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let rel = Path::new(key);
+        if key.is_empty() || !rel.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(format!("ローカルストアで使えないキーです: {}", key).into());
+        }
+        Ok(self.root.join(rel))
+    }
+
+    /// ディレクトリを再帰的にたどってオブジェクトを集める
+    fn walk(&self, dir: &Path, objects: &mut Vec<ObjectInfo>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.walk(&path, objects)?;
+                continue;
+            }
+            let Ok(rel) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let key = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            objects.push(file_info(&path, key)?);
+        }
+        Ok(())
+    }
+}
+
+impl ObjectStore for LocalStore {
+    fn get(&self, key: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(fs::read(self.path_for(key)?)?)
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, Box<dyn std::error::Error>> {
+        let mut objects = Vec::new();
+        if self.root.is_dir() {
+            self.walk(&self.root, &mut objects)?;
+        }
+        objects.retain(|o| o.key.starts_with(prefix));
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+        match fs::remove_file(self.path_for(key)?) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn head(&self, key: &str) -> Result<Option<ObjectInfo>, Box<dyn std::error::Error>> {
+        let path = self.path_for(key)?;
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(file_info(&path, key.to_string())?))
+    }
+
+    fn copy(&self, src_key: &str, dst_key: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let data = self.get(src_key)?;
+        self.put(dst_key, &data)
+    }
+}
+
+/// メモリ上の BTreeMap をバックエンドとするストア
+/// ※ ETag は S3 の単一 PUT と同じく内容の MD5 とする
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    objects: Mutex<BTreeMap<String, MemoryObject>>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryObject {
+    data: Vec<u8>,
+    last_modified: DateTime<Utc>,
+}
+
+impl MemoryStore {
+    /// 空のストアを生成する
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    fn info(key: &str, object: &MemoryObject) -> ObjectInfo {
+        ObjectInfo {
+            key: key.to_string(),
+            size: object.data.len() as u64,
+            e_tag: Some(md5_hex(&object.data)),
+            last_modified: Some(object.last_modified),
+            storage_class: None,
+        }
+    }
+
+    fn lock(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, BTreeMap<String, MemoryObject>>, Box<dyn std::error::Error>>
+    {
+        self.objects
+            .lock()
+            .map_err(|_| "MemoryStore のロックに失敗しました".into())
+    }
+}
+
+impl ObjectStore for MemoryStore {
+    fn get(&self, key: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        self.lock()?
+            .get(key)
+            .map(|o| o.data.clone())
+            .ok_or_else(|| format!("オブジェクトが存在しません: {}", key).into())
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let object = MemoryObject {
+            data: data.to_vec(),
+            last_modified: Utc::now(),
+        };
+        self.lock()?.insert(key.to_string(), object);
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, Box<dyn std::error::Error>> {
+        Ok(self
+            .lock()?
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, object)| MemoryStore::info(key, object))
+            .collect())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.lock()?.remove(key);
+        Ok(())
+    }
+
+    fn head(&self, key: &str) -> Result<Option<ObjectInfo>, Box<dyn std::error::Error>> {
+        Ok(self
+            .lock()?
+            .get(key)
+            .map(|object| MemoryStore::info(key, object)))
+    }
+
+    fn copy(&self, src_key: &str, dst_key: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let data = self.get(src_key)?;
+        self.put(dst_key, &data)
+    }
+}
+
+/// ローカルファイルの情報を ObjectInfo として返す
+fn file_info(path: &Path, key: String) -> io::Result<ObjectInfo> {
+    let metadata = fs::metadata(path)?;
+    Ok(ObjectInfo {
+        key,
+        size: metadata.len(),
+        e_tag: Some(md5_hex(&fs::read(path)?)),
+        last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+        storage_class: None,
+    })
+}
+
+fn md5_hex(data: &[u8]) -> String {
+    format!("{:x}", Md5::digest(data))
+}
@@ -0,0 +1,418 @@
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::delete::{delete_object_async, DeleteGuard};
+use super::download::{get_object_to_file_async, is_md5_etag, is_temp_path, GetOptions};
+use super::encryption::Encryption;
+use super::list::{list_stream, ObjectInfo};
+use super::progress::ProgressReporter;
+use super::ranged::file_etag;
+use super::upload::{put_file_async, PutOptions};
+use super::uri::S3Uri;
+use super::{run_concurrently, run_sync, BoxError};
+
+/// 同期のオプション
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// 対象にするパターン（空なら全て）。相対パスに対するグロブで、* は / を越えない
+    pub include: Vec<String>,
+    /// 対象から外すパターン。include より優先する
+    pub exclude: Vec<String>,
+    /// 転送元に無いファイルを転送先から削除する（--delete 相当）
+    pub delete: bool,
+    /// 実行せずに予定の操作を返すだけにする（--dryrun 相当）
+    pub dry_run: bool,
+    /// 同時に実行する転送の数
+    pub concurrency: usize,
+    /// 更新日時の代わりに ETag（MD5）で内容を比較する
+    /// ※ マルチパートや SSE-KMS の ETag は MD5 にならないため、その場合は更新日時で比較する
+    pub compare_etag: bool,
+    /// アップロード時のサーバー側暗号化、およびダウンロード時の SSE-C の鍵
+    pub encryption: Option<Encryption>,
+    /// 転送（アップロード・ダウンロード）の進捗の通知先
+    pub progress: Option<ProgressReporter>,
+    /// 保護対象のバケットのオブジェクトを delete で削除する場合の確認
+    pub guard: DeleteGuard,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        SyncOptions {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            delete: false,
+            dry_run: false,
+            concurrency: 8,
+            compare_etag: false,
+            encryption: None,
+            progress: None,
+            guard: DeleteGuard::default(),
+        }
+    }
+}
+
+/// 同期で実行する操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncAction {
+    /// ローカルファイルをアップロードする
+    Upload {
+        path: PathBuf,
+        bucket: String,
+        key: String,
+    },
+    /// オブジェクトをダウンロードする
+    Download {
+        bucket: String,
+        key: String,
+        path: PathBuf,
+    },
+    /// オブジェクトを削除する
+    DeleteRemote { bucket: String, key: String },
+    /// ローカルファイルを削除する
+    DeleteLocal { path: PathBuf },
+}
+
+impl fmt::Display for SyncAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncAction::Upload { path, bucket, key } => {
+                write!(f, "upload: {} to s3://{}/{}", path.display(), bucket, key)
+            }
+            SyncAction::Download { bucket, key, path } => {
+                write!(f, "download: s3://{}/{} to {}", bucket, key, path.display())
+            }
+            SyncAction::DeleteRemote { bucket, key } => {
+                write!(f, "delete: s3://{}/{}", bucket, key)
+            }
+            SyncAction::DeleteLocal { path } => write!(f, "delete: {}", path.display()),
+        }
+    }
+}
+
+/// ローカルディレクトリを S3 に同期する（非同期版）
+/// ※ サイズが違うか、ローカルの方が新しいファイルをアップロードする
+/// ※ 戻り値は実行した（dry_run の場合は予定した）操作の一覧
+pub async fn sync_up_async(
+    client: &Client,
+    local_dir: &Path,
+    s3_uri: &str,
+    options: &SyncOptions,
+) -> Result<Vec<SyncAction>, BoxError> {
+    let uri = S3Uri::from_str(s3_uri)?.to_dir();
+    let (bucket, prefix) = (uri.bucket().to_string(), uri.key().to_string());
+    let filter = Filter::new(&options.include, &options.exclude)?;
+    let locals = walk_local(local_dir, &filter)?;
+    let remotes = list_remote(client, &bucket, &prefix, &filter).await?;
+
+    let mut actions = Vec::new();
+    for (rel, local) in &locals {
+        let transfer = match remotes.get(rel) {
+            None => true,
+            Some(remote) => differs(local, remote, options.compare_etag, Direction::Up)?,
+        };
+        if transfer {
+            actions.push(SyncAction::Upload {
+                path: local.path.clone(),
+                bucket: bucket.clone(),
+                key: format!("{}{}", prefix, rel),
+            });
+        }
+    }
+    if options.delete {
+        if !options.dry_run {
+            options.guard.check(&bucket)?;
+        }
+        for rel in remotes.keys().filter(|rel| !locals.contains_key(*rel)) {
+            actions.push(SyncAction::DeleteRemote {
+                bucket: bucket.clone(),
+                key: format!("{}{}", prefix, rel),
+            });
+        }
+    }
+
+    execute(client, &actions, options).await?;
+    Ok(actions)
+}
+
+/// S3 をローカルディレクトリに同期する（非同期版）
+/// ※ サイズが違うか、S3 の方が新しいオブジェクトをダウンロードする
+/// ※ 戻り値は実行した（dry_run の場合は予定した）操作の一覧
+pub async fn sync_down_async(
+    client: &Client,
+    s3_uri: &str,
+    local_dir: &Path,
+    options: &SyncOptions,
+) -> Result<Vec<SyncAction>, BoxError> {
+    let uri = S3Uri::from_str(s3_uri)?.to_dir();
+    let (bucket, prefix) = (uri.bucket().to_string(), uri.key().to_string());
+    let filter = Filter::new(&options.include, &options.exclude)?;
+    let locals = walk_local(local_dir, &filter)?;
+    let remotes = list_remote(client, &bucket, &prefix, &filter).await?;
+
+    let mut actions = Vec::new();
+    for (rel, remote) in &remotes {
+        let transfer = match locals.get(rel) {
+            None => true,
+            Some(local) => differs(local, remote, options.compare_etag, Direction::Down)?,
+        };
+        if transfer {
+            actions.push(SyncAction::Download {
+                bucket: bucket.clone(),
+                key: remote.key.clone(),
+                path: local_path(local_dir, rel),
+            });
+        }
+    }
+    if options.delete {
+        for (_, local) in locals.iter().filter(|(rel, _)| !remotes.contains_key(*rel)) {
+            actions.push(SyncAction::DeleteLocal {
+                path: local.path.clone(),
+            });
+        }
+    }
+
+    execute(client, &actions, options).await?;
+    Ok(actions)
+}
+
+/// ローカルディレクトリを S3 に同期する
+pub fn sync_up(
+    local_dir: &Path,
+    s3_uri: &str,
+    options: &SyncOptions,
+) -> Result<Vec<SyncAction>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { sync_up_async(&s3, local_dir, s3_uri, options).await })
+}
+
+/// S3 をローカルディレクトリに同期する
+pub fn sync_down(
+    s3_uri: &str,
+    local_dir: &Path,
+    options: &SyncOptions,
+) -> Result<Vec<SyncAction>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { sync_down_async(&s3, s3_uri, local_dir, options).await })
+}
+
+/// 同期の向き
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Up,
+    Down,
+}
+
+/// ローカルファイルの情報
+#[derive(Debug, Clone)]
+pub(super) struct LocalFile {
+    pub(super) path: PathBuf,
+    pub(super) size: u64,
+    pub(super) modified: DateTime<Utc>,
+}
+
+/// include / exclude のパターン
+pub(super) struct Filter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl Filter {
+    pub(super) fn new(include: &[String], exclude: &[String]) -> Result<Self, BoxError> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(include)?)
+        };
+        Ok(Filter {
+            include,
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    /// 相対パスが同期対象かどうか
+    fn matches(&self, rel: &str) -> bool {
+        let included = self.include.as_ref().map_or(true, |set| set.is_match(rel));
+        included && !self.exclude.is_match(rel)
+    }
+}
+
+/// パターンの一覧から GlobSet を作る
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet, BoxError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+    }
+    Ok(builder.build()?)
+}
+
+/// ローカルディレクトリ配下のファイルを「/ 区切りの相対パス → 情報」で返す
+/// ※ ディレクトリが存在しない場合は空を返す
+/// ※ シンボリックリンクはファイルを指すものだけを対象にし、ディレクトリへのリンクは辿らない（循環を避けるため）
+/// ※ 中断したダウンロードの一時ファイル（.name.part）は対象にしない
+pub(super) fn walk_local(
+    root: &Path,
+    filter: &Filter,
+) -> Result<BTreeMap<String, LocalFile>, BoxError> {
+    let mut files = BTreeMap::new();
+    if !root.is_dir() {
+        return Ok(files);
+    }
+
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let metadata = fs::symlink_metadata(&path)?;
+            if metadata.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let metadata = if metadata.is_symlink() {
+                // リンク切れやディレクトリへのリンクは飛ばす
+                match fs::metadata(&path) {
+                    Ok(target) if target.is_file() => target,
+                    _ => continue,
+                }
+            } else {
+                metadata
+            };
+            if is_temp_path(&path) {
+                continue;
+            }
+            let rel = path
+                .strip_prefix(root)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if !filter.matches(&rel) {
+                continue;
+            }
+            files.insert(
+                rel,
+                LocalFile {
+                    path,
+                    size: metadata.len(),
+                    modified: DateTime::<Utc>::from(metadata.modified()?),
+                },
+            );
+        }
+    }
+    Ok(files)
+}
+
+/// プレフィックス配下のオブジェクトを「プレフィックスからの相対キー → 情報」で返す
+/// ※ "/" で終わるディレクトリ用のオブジェクトや、".." を含むキーは対象にしない
+pub(super) async fn list_remote(
+    client: &Client,
+    bucket: &str,
+    prefix: &str,
+    filter: &Filter,
+) -> Result<BTreeMap<String, ObjectInfo>, BoxError> {
+    let mut objects = BTreeMap::new();
+    let mut stream = list_stream(client, bucket, prefix, None);
+    while let Some(object) = stream.next_object().await {
+        let object = object?;
+        let Some(rel) = object.key.strip_prefix(prefix) else {
+            continue;
+        };
+        if rel.is_empty() || rel.ends_with('/') || rel.split('/').any(|c| c == "..") {
+            continue;
+        }
+        if filter.matches(rel) {
+            objects.insert(rel.to_string(), object);
+        }
+    }
+    Ok(objects)
+}
+
+/// 相対キーをローカルのパスに変換する
+fn local_path(root: &Path, rel: &str) -> PathBuf {
+    rel.split('/')
+        .fold(root.to_path_buf(), |path, c| path.join(c))
+}
+
+/// ローカルと S3 の内容が異なり、転送が必要かどうか
+/// ※ S3 の更新日時は秒単位のため、秒で比較する
+fn differs(
+    local: &LocalFile,
+    remote: &ObjectInfo,
+    compare_etag: bool,
+    direction: Direction,
+) -> Result<bool, BoxError> {
+    if local.size != remote.size {
+        return Ok(true);
+    }
+    if let (true, Some(e_tag)) = (compare_etag, remote.e_tag.as_deref()) {
+        if is_md5_etag(e_tag) {
+            return Ok(file_etag(&local.path, None)? != e_tag);
+        }
+    }
+    let Some(remote_modified) = remote.last_modified else {
+        return Ok(false);
+    };
+    let (local_secs, remote_secs) = (local.modified.timestamp(), remote_modified.timestamp());
+    Ok(match direction {
+        Direction::Up => local_secs > remote_secs,
+        Direction::Down => remote_secs > local_secs,
+    })
+}
+
+/// 操作を実行する（dry_run の場合は何もしない）
+async fn execute(
+    client: &Client,
+    actions: &[SyncAction],
+    options: &SyncOptions,
+) -> Result<(), BoxError> {
+    if options.dry_run {
+        return Ok(());
+    }
+
+    let tasks = actions.iter().cloned().map(|action| {
+        run_action(
+            client.clone(),
+            action,
+            options.encryption.clone(),
+            options.progress.clone(),
+            options.guard.clone(),
+        )
+    });
+    run_concurrently(tasks, options.concurrency, |_| Ok(())).await
+}
+
+/// 1 つの操作を実行する
+async fn run_action(
+    client: Client,
+    action: SyncAction,
+    encryption: Option<Encryption>,
+    progress: Option<ProgressReporter>,
+    guard: DeleteGuard,
+) -> Result<SyncAction, BoxError> {
+    match &action {
+        SyncAction::Upload { path, bucket, key } => {
+            let options = PutOptions {
+                encryption,
+                progress,
+                ..PutOptions::default()
+            };
+            put_file_async(&client, bucket, key, path, &options).await?;
+        }
+        SyncAction::Download { bucket, key, path } => {
+            let options = GetOptions {
+                encryption,
+                progress,
+                ..GetOptions::default()
+            };
+            get_object_to_file_async(&client, bucket, key, path, &options).await?;
+        }
+        SyncAction::DeleteRemote { bucket, key } => {
+            delete_object_async(&client, bucket, key, &guard).await?;
+        }
+        SyncAction::DeleteLocal { path } => fs::remove_file(path)?,
+    }
+    Ok(action)
+}
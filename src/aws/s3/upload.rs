@@ -0,0 +1,317 @@
+use aws_sdk_s3::operation::put_object::builders::PutObjectFluentBuilder;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{ObjectCannedAcl, StorageClass};
+use aws_sdk_s3::Client;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+use super::bandwidth::upload_byte_stream;
+use super::checksum::{checksum_headers, file_checksum, verify_checksum, ChecksumAlgorithm};
+use super::compression::{Compression, EncodingWriter};
+use super::encryption::{sse_headers, Encryption, EncryptionInfo};
+use super::multipart::{
+    effective_part_size, upload_file_multipart_async, MultipartOptions, StreamingUpload,
+};
+use super::progress::ProgressReporter;
+use super::{run_sync, trim_etag, BoxError};
+
+/// このサイズを超えるファイルは put_file でもマルチパートでアップロードする
+pub const MULTIPART_THRESHOLD: u64 = 1024 * 1024 * 1024;
+
+/// アップロード時に指定できるオプション
+#[derive(Debug, Clone, Default)]
+pub struct PutOptions {
+    /// Content-Type（None の場合は拡張子から推測する）
+    pub content_type: Option<String>,
+    /// ユーザー定義メタデータ（x-amz-meta-*）
+    pub metadata: HashMap<String, String>,
+    /// Cache-Control
+    pub cache_control: Option<String>,
+    /// Content-Encoding
+    pub content_encoding: Option<String>,
+    /// ストレージクラス
+    pub storage_class: Option<StorageClass>,
+    /// 既定 ACL
+    pub acl: Option<ObjectCannedAcl>,
+    /// サーバー側暗号化（None の場合はバケットの既定の暗号化に従う）
+    pub encryption: Option<Encryption>,
+    /// 送信時に計算して S3 に検証させるチェックサム（None の場合は SDK の既定に従う）
+    /// ※ マルチパートの場合はパートごとに計算し、オブジェクトには複合チェックサムが記録される
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// 進捗の通知先
+    /// ※ 送信したデータを 64 KiB ごとに通知する
+    pub progress: Option<ProgressReporter>,
+    /// 圧縮してアップロードする
+    /// ※ キーに拡張子（.gz / .zst）が無ければ付け足し、Content-Encoding を設定する
+    /// ※ Content-Type は圧縮前のファイル名・キーから推測する
+    pub compression: Option<Compression>,
+}
+
+/// アップロード結果を保持する構造体
+#[derive(Debug, Clone, PartialEq)]
+pub struct PutResult {
+    /// ETag（前後のダブルクォートは取り除いた値）
+    pub e_tag: Option<String>,
+    /// バージョン ID（バージョニングが有効なバケットのみ）
+    pub version_id: Option<String>,
+    /// 実際に適用された暗号化
+    pub encryption: Option<EncryptionInfo>,
+    /// S3 に記録されたチェックサム（PutOptions.checksum を指定した場合のみ）
+    pub checksum: Option<String>,
+}
+
+/// ローカルファイルをアップロードする（非同期版）
+/// ※ Content-Type はファイル名、次いでキーの拡張子から推測する
+/// ※ MULTIPART_THRESHOLD を超えるファイルはマルチパート（既定のパートサイズ・並列数）で送る
+/// ※ 圧縮する場合は、逐次圧縮しながらパートごとに送る（一時ファイルは作らない）
+pub async fn put_file_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &PutOptions,
+) -> Result<PutResult, BoxError> {
+    let Some(compression) = options.compression else {
+        return put_file_plain(client, bucket, key, path, options).await;
+    };
+    let (key, options) = compressed_target(compression, key, Some(path), options);
+    // 圧縮後のサイズは事前にわからないため、圧縮前のサイズからパートサイズを決める
+    let part_size = effective_part_size(
+        fs::metadata(path)?.len(),
+        MultipartOptions::default().part_size,
+    );
+    let mut upload = StreamingUpload::new(bucket, &key, &options, part_size);
+    let result = put_file_compressed(client, path, compression, &mut upload).await;
+    if result.is_err() {
+        upload.abort(client).await;
+    }
+    result
+}
+
+/// ファイルを逐次圧縮し、圧縮したデータがパートサイズに達するごとに送る
+/// ※ 圧縮後のデータがパートサイズに満たなければ、通常の PutObject で送る
+async fn put_file_compressed(
+    client: &Client,
+    path: &Path,
+    compression: Compression,
+    upload: &mut StreamingUpload,
+) -> Result<PutResult, BoxError> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut encoder = EncodingWriter::new(compression, Vec::new())?;
+    let mut chunk = vec![0u8; 1024 * 1024];
+    let mut buf = Vec::new();
+    loop {
+        let n = file.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        encoder.write_all(&chunk[..n])?;
+        buf.append(encoder.get_mut());
+        while buf.len() >= upload.part_size() {
+            let rest = buf.split_off(upload.part_size());
+            upload
+                .send_part(client, std::mem::replace(&mut buf, rest))
+                .await?;
+        }
+    }
+    buf.append(&mut encoder.finish()?);
+    upload.finish(client, buf).await
+}
+
+/// ローカルファイルを圧縮せずにアップロードする
+async fn put_file_plain(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &PutOptions,
+) -> Result<PutResult, BoxError> {
+    let size = fs::metadata(path)?.len();
+    if size > MULTIPART_THRESHOLD {
+        let options = MultipartOptions {
+            put: options.clone(),
+            ..MultipartOptions::default()
+        };
+        return upload_file_multipart_async(client, bucket, key, path, &options).await;
+    }
+
+    let content_type = infer_content_type(options, Some(path), key);
+    let checksum = match options.checksum {
+        Some(algorithm) => Some((algorithm, file_checksum(path, algorithm, None)?)),
+        None => None,
+    };
+    let body = upload_byte_stream(
+        ByteStream::from_path(path).await?,
+        options.progress.as_ref(),
+    );
+    let request = client.put_object().bucket(bucket).key(key).body(body);
+    let request = apply_options(request, options, content_type, checksum.as_ref())?;
+    send_put(key, size, request, checksum, options.progress.as_ref()).await
+}
+
+/// バイト列をアップロードする（非同期版）
+/// ※ Content-Type はキーの拡張子から推測する
+pub async fn put_bytes_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    bytes: Vec<u8>,
+    options: &PutOptions,
+) -> Result<PutResult, BoxError> {
+    let Some(compression) = options.compression else {
+        return put_bytes_plain(client, bucket, key, bytes, options).await;
+    };
+    let (key, options) = compressed_target(compression, key, None, options);
+    let bytes = compression.compress(&bytes)?;
+    put_bytes_plain(client, bucket, &key, bytes, &options).await
+}
+
+/// バイト列を圧縮せずにアップロードする
+async fn put_bytes_plain(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    bytes: Vec<u8>,
+    options: &PutOptions,
+) -> Result<PutResult, BoxError> {
+    let content_type = infer_content_type(options, None, key);
+    let checksum = options.checksum.map(|a| (a, a.checksum(&bytes)));
+    let size = bytes.len() as u64;
+    let request = client
+        .put_object()
+        .bucket(bucket)
+        .key(key)
+        .body(upload_byte_stream(
+            ByteStream::from(bytes),
+            options.progress.as_ref(),
+        ));
+    let request = apply_options(request, options, content_type, checksum.as_ref())?;
+    send_put(key, size, request, checksum, options.progress.as_ref()).await
+}
+
+/// ローカルファイルをアップロードする
+pub fn put_file(
+    bucket: &str,
+    key: &str,
+    path: &Path,
+    options: &PutOptions,
+) -> Result<PutResult, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { put_file_async(&s3, bucket, key, path, options).await })
+}
+
+/// バイト列をアップロードする
+pub fn put_bytes(
+    bucket: &str,
+    key: &str,
+    bytes: Vec<u8>,
+    options: &PutOptions,
+) -> Result<PutResult, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { put_bytes_async(&s3, bucket, key, bytes, options).await })
+}
+
+/// 拡張子から Content-Type を推測する
+pub fn guess_content_type(path: &Path) -> Option<String> {
+    mime_guess::from_path(path)
+        .first()
+        .map(|mime| mime.essence_str().to_string())
+}
+
+/// 指定が無ければ、ファイル名、次いでキーの拡張子から Content-Type を決める
+pub(super) fn infer_content_type(
+    options: &PutOptions,
+    path: Option<&Path>,
+    key: &str,
+) -> Option<String> {
+    options
+        .content_type
+        .clone()
+        .or_else(|| path.and_then(guess_content_type))
+        .or_else(|| guess_content_type(Path::new(key)))
+}
+
+/// 圧縮してアップロードする場合の、拡張子を付けたキーとオプションを返す
+/// ※ 返すオプションは Content-Type を確定させ、Content-Encoding を設定し、compression を外したもの
+pub(super) fn compressed_target(
+    compression: Compression,
+    key: &str,
+    path: Option<&Path>,
+    options: &PutOptions,
+) -> (String, PutOptions) {
+    let original_key = key.strip_suffix(compression.extension()).unwrap_or(key);
+    let options = PutOptions {
+        content_type: infer_content_type(options, path, original_key),
+        content_encoding: Some(compression.content_encoding().to_string()),
+        compression: None,
+        ..options.clone()
+    };
+    (compression.key_for(key), options)
+}
+
+/// PutOptions の内容をリクエストに反映する
+/// ※ Content-Type が推測できなかった場合は S3 の既定（binary/octet-stream）に任せる
+fn apply_options(
+    request: PutObjectFluentBuilder,
+    options: &PutOptions,
+    content_type: Option<String>,
+    checksum: Option<&(ChecksumAlgorithm, String)>,
+) -> Result<PutObjectFluentBuilder, BoxError> {
+    let metadata = (!options.metadata.is_empty()).then(|| options.metadata.clone());
+    let sse = sse_headers(options.encryption.as_ref())?;
+    let checksum = checksum_headers(checksum);
+    Ok(request
+        .set_content_type(content_type)
+        .set_metadata(metadata)
+        .set_cache_control(options.cache_control.clone())
+        .set_content_encoding(options.content_encoding.clone())
+        .set_storage_class(options.storage_class.clone())
+        .set_acl(options.acl.clone())
+        .set_server_side_encryption(sse.server_side_encryption)
+        .set_ssekms_key_id(sse.kms_key_id)
+        .set_ssekms_encryption_context(sse.kms_context)
+        .set_sse_customer_algorithm(sse.customer.algorithm)
+        .set_sse_customer_key(sse.customer.key)
+        .set_sse_customer_key_md5(sse.customer.key_md5)
+        .set_checksum_crc32_c(checksum.crc32c)
+        .set_checksum_sha256(checksum.sha256))
+}
+
+/// PutObject を送信して結果を取り出す
+/// ※ S3 が記録したチェックサムが、送信前に計算した値と一致するか検証する
+async fn send_put(
+    key: &str,
+    size: u64,
+    request: PutObjectFluentBuilder,
+    checksum: Option<(ChecksumAlgorithm, String)>,
+    progress: Option<&ProgressReporter>,
+) -> Result<PutResult, BoxError> {
+    if let Some(progress) = progress {
+        progress.file_started(key, size);
+    }
+    // 転送量はボディを送り出すたびに通知される
+    let resp = request.send().await?;
+    if let Some(progress) = progress {
+        progress.file_finished(key);
+    }
+    let stored = checksum.as_ref().and_then(|(algorithm, _)| {
+        algorithm.pick(
+            resp.checksum_crc32_c.as_deref(),
+            resp.checksum_sha256.as_deref(),
+        )
+    });
+    if let (Some((algorithm, expected)), Some(stored)) = (&checksum, stored) {
+        verify_checksum(key, *algorithm, expected, stored)?;
+    }
+    Ok(PutResult {
+        e_tag: resp.e_tag.as_deref().map(trim_etag),
+        version_id: resp.version_id,
+        encryption: EncryptionInfo::from_headers(
+            resp.server_side_encryption.as_ref(),
+            resp.ssekms_key_id.as_deref(),
+            resp.sse_customer_algorithm.as_deref(),
+        ),
+        checksum: stored.map(str::to_string),
+    })
+}
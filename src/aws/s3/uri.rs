@@ -0,0 +1,237 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+use super::error::S3Error;
+
+/// s3://bucket/key 形式の S3 URI
+/// ※ キーが空、または "/" で終わる場合は「ディレクトリ」（プレフィックス）として扱う
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct S3Uri {
+    bucket: String,
+    key: String,
+}
+
+impl S3Uri {
+    /// バケット名とキーから生成する（バケット名の命名規則を検証する）
+    pub fn new(bucket: &str, key: &str) -> Result<Self, S3Error> {
+        validate_bucket_name(bucket).map_err(|reason| S3Error::InvalidUri {
+            uri: format!("s3://{}/{}", bucket, key),
+            reason,
+        })?;
+        Ok(S3Uri {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+
+    /// https の URL（仮想ホスト形式・パス形式）から生成する
+    /// 例: https://bucket.s3.ap-northeast-1.amazonaws.com/key
+    ///     https://s3.ap-northeast-1.amazonaws.com/bucket/key
+    pub fn from_url(url: &str) -> Result<Self, S3Error> {
+        let invalid = |reason: &str| S3Error::InvalidUri {
+            uri: url.to_string(),
+            reason: reason.to_string(),
+        };
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .ok_or_else(|| invalid("https:// で始まっていません"))?;
+        let rest = rest.split(['?', '#']).next().unwrap_or_default();
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let host = host.to_ascii_lowercase();
+        let host = host.split(':').next().unwrap_or_default();
+        if !host.ends_with(".amazonaws.com") && !host.ends_with(".amazonaws.com.cn") {
+            return Err(invalid("S3 のホスト名ではありません"));
+        }
+
+        let labels: Vec<&str> = host.split('.').collect();
+        // バケット名にも "s3" や "s3-" で始まるラベルを使えるため、末尾側から探す
+        let is_s3_label = |label: &&str| *label == "s3" || label.starts_with("s3-");
+        let s3_index = labels
+            .iter()
+            .rposition(is_s3_label)
+            .ok_or_else(|| invalid("S3 のホスト名ではありません"))?;
+
+        let path = percent_decode(path).ok_or_else(|| invalid("URL エンコードが不正です"))?;
+        if s3_index == 0 {
+            // パス形式: 先頭のパス要素がバケット名
+            let (bucket, key) = path.split_once('/').unwrap_or((&path, ""));
+            S3Uri::new(bucket, key)
+        } else {
+            // 仮想ホスト形式: s3 より前のラベルがバケット名
+            S3Uri::new(&labels[..s3_index].join("."), &path)
+        }
+    }
+
+    /// バケット名
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// キー（プレフィックス）
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// ディレクトリ（キーが空、または "/" で終わる）かどうか
+    pub fn is_dir(&self) -> bool {
+        self.key.is_empty() || self.key.ends_with('/')
+    }
+
+    /// キーの末尾が "/" になるよう揃えた URI を返す（バケット直下はそのまま）
+    pub fn to_dir(&self) -> S3Uri {
+        let key = if self.is_dir() {
+            self.key.clone()
+        } else {
+            format!("{}/", self.key)
+        };
+        S3Uri {
+            bucket: self.bucket.clone(),
+            key,
+        }
+    }
+
+    /// パス要素を連結した URI を返す（区切りの "/" は重複しないように補う）
+    pub fn join(&self, path: &str) -> S3Uri {
+        let path = path.trim_start_matches('/');
+        let key = if self.is_dir() {
+            format!("{}{}", self.key, path)
+        } else {
+            format!("{}/{}", self.key, path)
+        };
+        S3Uri {
+            bucket: self.bucket.clone(),
+            key,
+        }
+    }
+
+    /// 親ディレクトリの URI を返す（バケット直下の場合は None）
+    pub fn parent(&self) -> Option<S3Uri> {
+        if self.key.is_empty() {
+            return None;
+        }
+        let trimmed = self.key.trim_end_matches('/');
+        let key = match trimmed.rfind('/') {
+            Some(i) => trimmed[..=i].to_string(),
+            None => String::new(),
+        };
+        Some(S3Uri {
+            bucket: self.bucket.clone(),
+            key,
+        })
+    }
+
+    /// キーの最後の要素（ディレクトリの場合は None）
+    pub fn file_name(&self) -> Option<&str> {
+        if self.is_dir() {
+            return None;
+        }
+        self.key.rsplit('/').next()
+    }
+}
+
+impl fmt::Display for S3Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "s3://{}/{}", self.bucket, self.key)
+    }
+}
+
+/// s3://bucket/key 形式に加えて、https の URL も受け付ける
+impl FromStr for S3Uri {
+    type Err = S3Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("https://") || s.starts_with("http://") {
+            return S3Uri::from_url(s);
+        }
+        let rest = s.strip_prefix("s3://").ok_or_else(|| S3Error::InvalidUri {
+            uri: s.to_string(),
+            reason: "s3:// で始まっていません".to_string(),
+        })?;
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        S3Uri::new(bucket, key)
+    }
+}
+
+impl Serialize for S3Uri {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for S3Uri {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// 汎用バケットの命名規則を検証する
+/// ※ エラーの場合は理由を返す
+pub fn validate_bucket_name(bucket: &str) -> Result<(), String> {
+    if !(3..=63).contains(&bucket.len()) {
+        return Err("バケット名は 3〜63 文字です".to_string());
+    }
+    if !bucket
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-')
+    {
+        return Err("バケット名に使えるのは小文字・数字・ピリオド・ハイフンです".to_string());
+    }
+    let is_alnum = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+    if !bucket.starts_with(is_alnum) || !bucket.ends_with(is_alnum) {
+        return Err("バケット名は英小文字か数字で始まり、終わる必要があります".to_string());
+    }
+    if bucket.contains("..") {
+        return Err("バケット名にピリオドを連続して使うことはできません".to_string());
+    }
+    if bucket.split('.').count() == 4 && bucket.split('.').all(|p| p.parse::<u8>().is_ok()) {
+        return Err("バケット名を IP アドレスの形式にすることはできません".to_string());
+    }
+    if ["xn--", "sthree-", "amzn-s3-demo-"]
+        .iter()
+        .any(|p| bucket.starts_with(p))
+    {
+        return Err("予約されたプレフィックスで始まっています".to_string());
+    }
+    if ["-s3alias", "--ol-s3", ".mrap", "--table-s3"]
+        .iter()
+        .any(|s| bucket.ends_with(s))
+    {
+        return Err("予約されたサフィックスで終わっています".to_string());
+    }
+    Ok(())
+}
+
+/// キーを URL エンコードする（"/" はそのまま残す）
+pub(super) fn percent_encode_key(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for b in key.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// URL エンコードされた文字列を元に戻す（不正な場合は None）
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
@@ -0,0 +1,252 @@
+use aws_sdk_s3::Client;
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::bucket::client_for_bucket;
+use super::copy::{copy_version, CopyOptions};
+use super::download::{download_to_file, download_to_writer, DownloadInfo, GetOptions};
+use super::{run_concurrently, run_sync, to_chrono, trim_etag, BoxError};
+
+/// オブジェクトのバージョン（削除マーカーを含む）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectVersion {
+    pub key: String,
+    /// バージョン ID（バージョニング有効化前のオブジェクトは "null"）
+    pub version_id: String,
+    /// 最新バージョンかどうか
+    pub is_latest: bool,
+    /// 削除マーカーかどうか
+    pub is_delete_marker: bool,
+    /// サイズ（バイト）（削除マーカーは 0）
+    pub size: u64,
+    /// ETag（前後のダブルクォートは取り除いた値）（削除マーカーは None）
+    pub e_tag: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// ポイントインタイム復元のオプション
+#[derive(Debug, Clone)]
+pub struct RestoreOptions {
+    /// true の場合は復元するバージョンを返すだけで変更しない
+    pub dry_run: bool,
+    /// 同時に実行するコピーの数
+    pub concurrency: usize,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        RestoreOptions {
+            dry_run: false,
+            concurrency: 8,
+        }
+    }
+}
+
+/// プレフィックス配下の全バージョンと削除マーカーを取得する（非同期版）
+/// ※ キーの昇順、同じキーの中では新しい順に並べて返す
+pub async fn list_versions_async(
+    client: &Client,
+    bucket: &str,
+    prefix: &str,
+) -> Result<Vec<ObjectVersion>, BoxError> {
+    let mut versions = Vec::new();
+    let mut key_marker = None;
+    let mut version_id_marker = None;
+    loop {
+        let page = client
+            .list_object_versions()
+            .bucket(bucket)
+            .prefix(prefix)
+            .set_key_marker(key_marker.take())
+            .set_version_id_marker(version_id_marker.take())
+            .send()
+            .await?;
+        for v in page.versions.unwrap_or_default() {
+            let (Some(key), Some(version_id)) = (v.key, v.version_id) else {
+                continue;
+            };
+            versions.push(ObjectVersion {
+                key,
+                version_id,
+                is_latest: v.is_latest.unwrap_or(false),
+                is_delete_marker: false,
+                size: v.size.unwrap_or(0).max(0) as u64,
+                e_tag: v.e_tag.as_deref().map(trim_etag),
+                last_modified: v.last_modified.as_ref().and_then(to_chrono),
+            });
+        }
+        for m in page.delete_markers.unwrap_or_default() {
+            let (Some(key), Some(version_id)) = (m.key, m.version_id) else {
+                continue;
+            };
+            versions.push(ObjectVersion {
+                key,
+                version_id,
+                is_latest: m.is_latest.unwrap_or(false),
+                is_delete_marker: true,
+                size: 0,
+                e_tag: None,
+                last_modified: m.last_modified.as_ref().and_then(to_chrono),
+            });
+        }
+        if !page.is_truncated.unwrap_or(false) {
+            break;
+        }
+        key_marker = page.next_key_marker;
+        version_id_marker = page.next_version_id_marker;
+    }
+    versions.sort_by(|a, b| {
+        a.key
+            .cmp(&b.key)
+            .then(b.last_modified.cmp(&a.last_modified))
+    });
+    Ok(versions)
+}
+
+/// 1 つのキーの全バージョンと削除マーカーを取得する（非同期版）
+/// ※ 新しい順に並べて返す
+pub async fn list_key_versions_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> Result<Vec<ObjectVersion>, BoxError> {
+    let mut versions = list_versions_async(client, bucket, key).await?;
+    versions.retain(|v| v.key == key);
+    Ok(versions)
+}
+
+/// バージョンを指定してオブジェクトの内容をバイト列として取得する（非同期版）
+pub async fn get_object_version_bytes_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    version_id: &str,
+    options: &GetOptions,
+) -> Result<Vec<u8>, BoxError> {
+    let mut buf = Vec::new();
+    download_to_writer(client, bucket, key, Some(version_id), &mut buf, options).await?;
+    Ok(buf)
+}
+
+/// バージョンを指定してオブジェクトをファイルに保存する（非同期版）
+pub async fn get_object_version_to_file_async(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    version_id: &str,
+    path: &Path,
+    options: &GetOptions,
+) -> Result<DownloadInfo, BoxError> {
+    download_to_file(client, bucket, key, Some(version_id), path, options).await
+}
+
+/// 各キーについて、timestamp 時点で最新だったバージョンを求める
+/// ※ 現在の最新バージョンと同じもの、その時点で存在しなかった（削除マーカーやバージョンが無い）キーは含まない
+/// ※ versions は list_versions と同じく、キーごとに新しい順に並んでいるものとする
+pub fn restore_plan(versions: &[ObjectVersion], timestamp: DateTime<Utc>) -> Vec<ObjectVersion> {
+    let mut by_key: BTreeMap<&str, Vec<&ObjectVersion>> = BTreeMap::new();
+    for v in versions {
+        by_key.entry(&v.key).or_default().push(v);
+    }
+    by_key
+        .into_values()
+        .filter_map(|versions| {
+            let target = versions
+                .iter()
+                .filter(|v| v.last_modified.is_some_and(|t| t <= timestamp))
+                // 更新日時は秒単位のため、同時刻のバージョンは一覧で先にある（新しい）ものを選ぶ
+                .min_by_key(|v| Reverse(v.last_modified))?;
+            (!target.is_delete_marker && !target.is_latest).then(|| (*target).clone())
+        })
+        .collect()
+}
+
+/// プレフィックス配下の各キーを timestamp 時点の内容に戻す（非同期版）
+/// ※ その時点のバージョンを最新バージョンとしてコピーし直すため、現在のバージョンも履歴に残る
+/// ※ timestamp 以降に作成されたキーは削除しない
+/// ※ 戻り値は復元した（dry_run の場合は復元する）バージョンの一覧
+pub async fn restore_prefix_to_async(
+    client: &Client,
+    bucket: &str,
+    prefix: &str,
+    timestamp: DateTime<Utc>,
+    options: &RestoreOptions,
+) -> Result<Vec<ObjectVersion>, BoxError> {
+    let client = client_for_bucket(client, bucket).await?;
+    let versions = list_versions_async(&client, bucket, prefix).await?;
+    let plan = restore_plan(&versions, timestamp);
+    if options.dry_run {
+        return Ok(plan);
+    }
+
+    let copy_options = CopyOptions {
+        concurrency: 1,
+        ..CopyOptions::default()
+    };
+    let tasks = plan.iter().cloned().map(|v| {
+        let client = client.clone();
+        let bucket = bucket.to_string();
+        let copy_options = copy_options.clone();
+        async move {
+            copy_version(&client, &bucket, &v.key, &v.version_id, &copy_options).await?;
+            Ok(())
+        }
+    });
+    run_concurrently(tasks, options.concurrency, |_| Ok(())).await?;
+    Ok(plan)
+}
+
+/// プレフィックス配下の全バージョンと削除マーカーを取得する
+pub fn list_versions(
+    bucket: &str,
+    prefix: &str,
+) -> Result<Vec<ObjectVersion>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { list_versions_async(&s3, bucket, prefix).await })
+}
+
+/// 1 つのキーの全バージョンと削除マーカーを取得する
+pub fn list_key_versions(
+    bucket: &str,
+    key: &str,
+) -> Result<Vec<ObjectVersion>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move { list_key_versions_async(&s3, bucket, key).await })
+}
+
+/// バージョンを指定してオブジェクトの内容をバイト列として取得する
+pub fn get_object_version_bytes(
+    bucket: &str,
+    key: &str,
+    version_id: &str,
+    options: &GetOptions,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move {
+        get_object_version_bytes_async(&s3, bucket, key, version_id, options).await
+    })
+}
+
+/// バージョンを指定してオブジェクトをファイルに保存する
+pub fn get_object_version_to_file(
+    bucket: &str,
+    key: &str,
+    version_id: &str,
+    path: &Path,
+    options: &GetOptions,
+) -> Result<DownloadInfo, Box<dyn std::error::Error>> {
+    run_sync(|s3| async move {
+        get_object_version_to_file_async(&s3, bucket, key, version_id, path, options).await
+    })
+}
+
+/// プレフィックス配下の各キーを timestamp 時点の内容に戻す
+pub fn restore_prefix_to(
+    bucket: &str,
+    prefix: &str,
+    timestamp: DateTime<Utc>,
+    options: &RestoreOptions,
+) -> Result<Vec<ObjectVersion>, Box<dyn std::error::Error>> {
+    run_sync(
+        |s3| async move { restore_prefix_to_async(&s3, bucket, prefix, timestamp, options).await },
+    )
+}
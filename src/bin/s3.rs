@@ -0,0 +1,110 @@
+use rust_std_wrapper::aws::s3::{diff_prefixes, inventory_report, DiffOptions, InventoryOptions};
+use std::fs;
+
+const USAGE: &str = "\
+使い方: s3 <サブコマンド> [引数]
+
+サブコマンド:
+  inventory <s3://bucket/prefix> [--format json|csv] [--top N] [--output FILE]
+      バケット・プレフィックス配下のオブジェクト数と容量を集計する
+      --format  出力形式（既定: json）
+      --top     サイズの大きいオブジェクトを何件載せるか（既定: 10）
+      --output  出力先のファイル（省略時は標準出力）
+  diff <左: s3://bucket/prefix または DIR> <右: 同左> [--size-only] [--exclude PATTERN]...
+      2 つの場所を比較し、差分があれば終了コード 1 で終了する
+      --size-only  ETag・チェックサムで内容を比較せず、サイズだけで比較する
+      --exclude    比較から外すパターン（相対パスに対するグロブ）";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("inventory") => inventory(&args[1..]),
+        Some("diff") => diff(&args[1..]),
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(USAGE.into()),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// inventory サブコマンド
+fn inventory(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut uri = None;
+    let mut format = "json".to_string();
+    let mut output = None;
+    let mut options = InventoryOptions::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = next_value(&mut args, arg)?,
+            "--top" => options.top_n = next_value(&mut args, arg)?.parse()?,
+            "--output" => output = Some(next_value(&mut args, arg)?),
+            _ if arg.starts_with("--") => {
+                return Err(format!("不明なオプションです: {}", arg).into())
+            }
+            _ if uri.is_none() => uri = Some(arg.clone()),
+            _ => return Err(format!("引数が多すぎます: {}", arg).into()),
+        }
+    }
+    let uri = uri.ok_or("集計する S3 URI を指定してください")?;
+    if format != "json" && format != "csv" {
+        return Err(format!("不明な出力形式です: {}（json または csv）", format).into());
+    }
+
+    let report = inventory_report(&uri, &options)?;
+    let text = if format == "csv" {
+        report.to_csv()
+    } else {
+        report.to_json()? + "\n"
+    };
+    match output {
+        Some(path) => fs::write(path, text)?,
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// diff サブコマンド
+fn diff(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut locations = Vec::new();
+    let mut options = DiffOptions::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size-only" => options.compare_content = false,
+            "--exclude" => options.exclude.push(next_value(&mut args, arg)?),
+            _ if arg.starts_with("--") => {
+                return Err(format!("不明なオプションです: {}", arg).into())
+            }
+            _ if locations.len() < 2 => locations.push(arg.clone()),
+            _ => return Err(format!("引数が多すぎます: {}", arg).into()),
+        }
+    }
+    let [left, right] = locations.as_slice() else {
+        return Err("比較する 2 つの場所を指定してください".into());
+    };
+
+    let diff = diff_prefixes(left, right, &options)?;
+    print!("{}", diff);
+    if !diff.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// オプションの値を取り出す
+fn next_value<'a>(
+    args: &mut impl Iterator<Item = &'a String>,
+    option: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    args.next()
+        .cloned()
+        .ok_or_else(|| format!("{} に値を指定してください", option).into())
+}
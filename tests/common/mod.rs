@@ -0,0 +1,222 @@
+//! 結合テストで共有するヘルパー
+//! ※ テストごとに使う関数が異なるため、未使用の警告は出さない
+#![allow(dead_code)]
+
+use aws_sdk_s3::config::retry::RetryConfig;
+use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+use aws_sdk_s3::{Client, Config};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 一時ディレクトリの下に、テストごとに重ならないディレクトリを作る
+pub fn temp_dir(prefix: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("{}_{}", prefix, nanos));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// S3 に接続する前に終わる処理のテスト用のクライアント
+pub fn dummy_client() -> Client {
+    Client::from_conf(
+        Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .build(),
+    )
+}
+
+/// モックの応答
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self::bytes(status, body.as_bytes().to_vec())
+    }
+
+    /// バイナリのボディを返す応答
+    pub fn bytes(status: u16, body: Vec<u8>) -> Self {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+}
+
+/// モックが受け取ったリクエスト
+pub struct MockRequest<'a> {
+    pub method: &'a str,
+    pub target: &'a str,
+    /// ヘッダー（名前は小文字）
+    pub headers: &'a [(String, String)],
+    pub body: &'a [u8],
+}
+
+impl MockRequest<'_> {
+    /// ヘッダーの値（name は小文字で指定する）
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// 受け取ったリクエスト
+struct Request {
+    /// "メソッド ターゲット"
+    line: String,
+    /// ヘッダー（名前は小文字）
+    headers: Vec<(String, String)>,
+}
+
+/// ローカルで応答する S3 のモック
+/// ※ handler はメソッドとリクエストターゲット（"/bucket/key?query"）から応答を決める
+/// ※ リクエストの数を数えられるよう、SDK の再試行は無効にする
+pub struct MockS3 {
+    pub client: Client,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockS3 {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&str, &str) -> MockResponse + Send + Sync + 'static,
+    {
+        Self::start_with_request(move |request| handler(request.method, request.target))
+    }
+
+    /// ヘッダーやボディも含めたリクエスト全体から応答を決めるモックを起動する
+    pub fn start_with_request<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let (recorded, handler) = (requests.clone(), Arc::new(handler));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (recorded, handler) = (recorded.clone(), handler.clone());
+                let stream = stream.unwrap();
+                std::thread::spawn(move || serve(stream, &*handler, &recorded));
+            }
+        });
+
+        let client = Client::from_conf(
+            Config::builder()
+                .behavior_version(BehaviorVersion::latest())
+                .region(Region::new("us-east-1"))
+                .endpoint_url(url)
+                .force_path_style(true)
+                .credentials_provider(Credentials::new("key", "secret", None, None, "test"))
+                .retry_config(RetryConfig::disabled())
+                .build(),
+        );
+        MockS3 { client, requests }
+    }
+
+    /// 指定したメソッドで、ターゲットに part を含むリクエストの数
+    pub fn count(&self, method: &str, part: &str) -> usize {
+        let requests = self.requests.lock().unwrap();
+        requests.iter().filter(|r| r.matches(method, part)).count()
+    }
+
+    /// 指定したメソッドで、ターゲットに part を含むリクエストのヘッダー name の値（受け取った順）
+    pub fn header_values(&self, method: &str, part: &str, name: &str) -> Vec<String> {
+        let requests = self.requests.lock().unwrap();
+        requests
+            .iter()
+            .filter(|r| r.matches(method, part))
+            .filter_map(|r| {
+                r.headers
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, v)| v.clone())
+            })
+            .collect()
+    }
+}
+
+impl Request {
+    fn matches(&self, method: &str, part: &str) -> bool {
+        self.line.starts_with(&format!("{} ", method)) && self.line.contains(part)
+    }
+}
+
+/// 1 つの接続のリクエストに順に応答する
+fn serve(
+    stream: TcpStream,
+    handler: &dyn Fn(&MockRequest) -> MockResponse,
+    recorded: &Mutex<Vec<Request>>,
+) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+            return;
+        }
+        let (mut length, mut headers) = (0, Vec::new());
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line.split_once(':').unwrap();
+            let (name, value) = (name.to_ascii_lowercase(), value.trim().to_string());
+            match name.as_str() {
+                "content-length" => length = value.parse().unwrap(),
+                "expect" => writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").unwrap(),
+                _ => {}
+            }
+            headers.push((name, value));
+        }
+        let mut body = vec![0u8; length];
+        reader.read_exact(&mut body).unwrap();
+
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = (parts.next().unwrap(), parts.next().unwrap());
+        let response = handler(&MockRequest {
+            method,
+            target,
+            headers: &headers,
+            body: &body,
+        });
+        recorded.lock().unwrap().push(Request {
+            line: format!("{} {}", method, target),
+            headers,
+        });
+        let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+        if !response.headers.iter().any(|(n, _)| *n == "Content-Length") {
+            head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+        }
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        writer.write_all(head.as_bytes()).unwrap();
+        if method != "HEAD" {
+            writer.write_all(&response.body).unwrap();
+        }
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use aws_sdk_s3::primitives::ByteStream;
+use rust_std_wrapper::aws::s3::{set_bandwidth_limit, throttled_byte_stream};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_throttled_upload_body() {
+        const RATE: u64 = 512 * 1024;
+        let data = vec![7u8; 3 * RATE as usize];
+        set_bandwidth_limit(Some(RATE));
+
+        // 再送できること（SDK のリトライ用）と、内容を変えないことを確認する
+        let retryable = throttled_byte_stream(ByteStream::from(data.clone()));
+        assert_eq!(retryable.bytes(), Some(data.as_slice()));
+        assert!(retryable.into_inner().try_clone().is_some());
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let start = Instant::now();
+        let mut stream = throttled_byte_stream(ByteStream::from(data.clone()));
+        let mut received = 0u64;
+        rt.block_on(async {
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.unwrap();
+                // 一度に送り出すのは 64 KiB まで
+                assert!(chunk.len() <= 64 * 1024);
+                received += chunk.len() as u64;
+                // 1 秒分のバーストと 1 区切りを除いて、経過時間あたりの量を超えない
+                let allowed = RATE as f64 * (start.elapsed().as_secs_f64() + 1.0) + 64.0 * 1024.0;
+                assert!(
+                    received as f64 <= allowed,
+                    "{} bytes after {:?}",
+                    received,
+                    start.elapsed()
+                );
+            }
+        });
+        set_bandwidth_limit(None);
+
+        assert_eq!(received, data.len() as u64);
+        // バースト分（1 秒）を除いた 2 秒分はかかる
+        assert!(start.elapsed().as_secs_f64() >= 1.8);
+    }
+}
@@ -0,0 +1,47 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::list_buckets_async;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockResponse, MockS3};
+    use super::*;
+
+    #[test]
+    fn test_list_buckets_region() {
+        // a-bucket は東京リージョン、b-bucket は GetBucketLocation の権限が無い
+        let mock = MockS3::start(|_, target| {
+            if target.starts_with("/a-bucket/?location") {
+                MockResponse::new(
+                    200,
+                    "<LocationConstraint>ap-northeast-1</LocationConstraint>",
+                )
+            } else if target.starts_with("/b-bucket/?location") {
+                MockResponse::new(
+                    403,
+                    "<Error><Code>AccessDenied</Code><Message>denied</Message></Error>",
+                )
+            } else {
+                MockResponse::new(
+                    200,
+                    "<ListAllMyBucketsResult><Buckets>\
+                     <Bucket><Name>a-bucket</Name><CreationDate>2026-01-01T00:00:00.000Z</CreationDate></Bucket>\
+                     <Bucket><Name>b-bucket</Name><CreationDate>2026-02-01T00:00:00.000Z</CreationDate></Bucket>\
+                     </Buckets></ListAllMyBucketsResult>",
+                )
+            }
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // 1 つのバケットでリージョンを取得できなくても、一覧は返す
+        let buckets = rt.block_on(list_buckets_async(&mock.client)).unwrap();
+        let names: Vec<_> = buckets.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["a-bucket", "b-bucket"]);
+        assert_eq!(buckets[0].region.as_deref(), Some("ap-northeast-1"));
+        assert_eq!(buckets[1].region, None);
+        assert!(buckets[0].creation_date.is_some());
+        assert_eq!(mock.count("GET", "location"), 2);
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{CacheOptions, S3Cache};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{dummy_client, temp_dir};
+    use super::*;
+    use std::fs;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_offline_cache() {
+        let dir = temp_dir("s3_cache");
+        // 前回のプロセスが残した索引（b.txt はファイルが失われている）
+        fs::write(dir.join("a-file"), b"cached").unwrap();
+        let index = r#"{
+            "bucket/a.txt": {"bucket": "bucket", "key": "a.txt", "e_tag": "etag-a", "size": 6,
+                "last_used": {"secs_since_epoch": 1, "nanos_since_epoch": 0}, "file": "a-file"},
+            "bucket/b.txt": {"bucket": "bucket", "key": "b.txt", "e_tag": "etag-b", "size": 3,
+                "last_used": {"secs_since_epoch": 2, "nanos_since_epoch": 0}, "file": "b-file"}
+        }"#;
+        fs::write(dir.join("index.json"), index).unwrap();
+
+        let options = CacheOptions {
+            offline: true,
+            ..CacheOptions::default()
+        };
+        let cache = S3Cache::open(&dir, options).unwrap();
+        let entries = cache.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].e_tag, "etag-a");
+        assert_eq!(cache.size(), 6);
+
+        // オフラインではキャッシュだけを使う（S3 には接続しない）
+        let client = dummy_client();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let bytes = rt
+            .block_on(cache.get_bytes_async(&client, "bucket", "a.txt"))
+            .unwrap();
+        assert_eq!(bytes, b"cached");
+        assert!(rt
+            .block_on(cache.get_bytes_async(&client, "bucket", "b.txt"))
+            .is_err());
+        // 使った日時が更新される
+        assert!(cache.entries()[0].last_used > UNIX_EPOCH + std::time::Duration::from_secs(2));
+
+        cache.clear().unwrap();
+        assert!(cache.entries().is_empty());
+        assert!(!dir.join("a-file").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{get_object_bytes_async, ChecksumAlgorithm, GetOptions, S3Error};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockResponse, MockS3};
+    use super::*;
+
+    #[test]
+    fn test_checksum() {
+        // CRC32C("123456789") = 0xE3069283
+        assert_eq!(ChecksumAlgorithm::Crc32c.checksum(b"123456789"), "4waSgw==");
+        assert_eq!(ChecksumAlgorithm::Crc32c.checksum(b""), "AAAAAA==");
+        assert_eq!(
+            ChecksumAlgorithm::Sha256.checksum(b""),
+            "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+        );
+    }
+
+    #[test]
+    fn test_mismatch_message() {
+        let e = S3Error::ChecksumMismatch {
+            key: "a.txt".to_string(),
+            algorithm: ChecksumAlgorithm::Crc32c.to_string(),
+            expected: "4waSgw==".to_string(),
+            actual: "AAAAAA==".to_string(),
+        };
+        assert_eq!(
+            e.to_string(),
+            "a.txt の CRC32C チェックサムが一致しません（期待値: 4waSgw==, 実際: AAAAAA==）"
+        );
+    }
+
+    /// 1 パート目の大きさと、CRC32C の複合チェックサムを返すモック
+    fn mock_s3(first_part_size: &'static str, checksum: String) -> MockS3 {
+        MockS3::start(move |method, target| match method {
+            "HEAD" if target.contains("partNumber=1") => {
+                MockResponse::new(206, "").header("Content-Length", first_part_size)
+            }
+            _ => MockResponse::new(200, "hello world")
+                .header("ETag", "\"0123456789abcdef-3\"")
+                .header("x-amz-checksum-crc32c", &checksum)
+                .header("x-amz-checksum-type", "COMPOSITE"),
+        })
+    }
+
+    fn get(mock: &MockS3) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        let options = GetOptions {
+            checksum: Some(ChecksumAlgorithm::Crc32c),
+            ..GetOptions::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(get_object_bytes_async(
+            &mock.client,
+            "bucket",
+            "a.txt",
+            &options,
+        ))
+    }
+
+    #[test]
+    fn test_unverifiable_part_layout() {
+        // 3 パートの複合チェックサムだが、1 パート目（6 バイト）から推定すると 11 バイトは 2 パートになる
+        let mock = mock_s3("6", "AAAAAA==-3".to_string());
+        // 検証できないことを成功として扱わない
+        assert_eq!(
+            get(&mock).unwrap_err().downcast_ref::<S3Error>(),
+            Some(&S3Error::ChecksumUnverifiable {
+                key: "a.txt".to_string(),
+                algorithm: "CRC32C".to_string(),
+                expected_parts: 3,
+                actual_parts: 2,
+            })
+        );
+        assert_eq!(mock.count("HEAD", "partNumber=1"), 1);
+
+        // パート数が同じでも（4, 4, 3 バイトと推定するが、実際は 4, 2, 5 バイトなど）、
+        // 複合チェックサムが一致しなければ破損とは断定しない
+        let mock = mock_s3("4", "AAAAAA==-3".to_string());
+        assert_eq!(
+            get(&mock).unwrap_err().downcast_ref::<S3Error>(),
+            Some(&S3Error::ChecksumUnverifiable {
+                key: "a.txt".to_string(),
+                algorithm: "CRC32C".to_string(),
+                expected_parts: 3,
+                actual_parts: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_composite_checksum() {
+        // 4, 4, 3 バイトに分けてアップロードしたオブジェクトの複合チェックサム
+        let parts: Vec<u8> = [&b"hell"[..], b"o wo", b"rld"]
+            .iter()
+            .flat_map(|part| crc32c::crc32c(part).to_be_bytes())
+            .collect();
+        let composite = format!("{}-3", ChecksumAlgorithm::Crc32c.checksum(&parts));
+
+        let mock = mock_s3("4", composite);
+        assert_eq!(get(&mock).unwrap(), b"hello world");
+    }
+}
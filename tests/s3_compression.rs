@@ -0,0 +1,83 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{put_file_async, Compression, PutOptions};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{temp_dir, MockResponse, MockS3};
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_detect() {
+        // Content-Encoding を優先する
+        assert_eq!(
+            Compression::detect(Some("zstd"), "logs/app.log.gz"),
+            Some(Compression::Zstd)
+        );
+        assert_eq!(
+            Compression::detect(Some("identity, GZIP"), "logs/app.log"),
+            Some(Compression::Gzip)
+        );
+        // 次いでキーの拡張子
+        assert_eq!(
+            Compression::detect(None, "logs/app.log.gz"),
+            Some(Compression::Gzip)
+        );
+        assert_eq!(
+            Compression::detect(Some("identity"), "logs/app.log.zst"),
+            Some(Compression::Zstd)
+        );
+        assert_eq!(Compression::detect(None, "logs/app.log"), None);
+    }
+
+    #[test]
+    fn test_key_for() {
+        assert_eq!(Compression::Gzip.key_for("logs/app.log"), "logs/app.log.gz");
+        assert_eq!(
+            Compression::Gzip.key_for("logs/app.log.gz"),
+            "logs/app.log.gz"
+        );
+        assert_eq!(
+            Compression::Zstd.key_for("logs/app.log"),
+            "logs/app.log.zst"
+        );
+        assert_eq!(Compression::Zstd.content_encoding(), "zstd");
+    }
+
+    #[test]
+    fn test_put_file_compressed() {
+        let dir = temp_dir("s3_compression");
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let options = PutOptions {
+            compression: Some(Compression::Zstd),
+            ..PutOptions::default()
+        };
+
+        // 圧縮後がパートサイズに満たなければ、PutObject 1 回で送る
+        let path = dir.join("small.log");
+        fs::write(&path, "hello\n".repeat(1000)).unwrap();
+        let mock = MockS3::start(|_, _| MockResponse::new(200, "").header("ETag", "\"e\""));
+        rt.block_on(put_file_async(
+            &mock.client,
+            "bucket",
+            "logs/small.log",
+            &path,
+            &options,
+        ))
+        .unwrap();
+        assert_eq!(mock.count("PUT", "/bucket/logs/small.log.zst"), 1);
+        assert_eq!(mock.count("POST", "uploads"), 0);
+
+        // 一時ファイルは作らない
+        let entries: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, ["small.log"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{move_object_async, CopyOptions, DeleteGuard, S3Error};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{dummy_client, MockResponse, MockS3};
+    use super::*;
+
+    #[test]
+    fn test_move_guards() {
+        // どちらの検証も S3 に接続する前に行う
+        let client = dummy_client();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let options = CopyOptions {
+            guard: DeleteGuard {
+                confirm: None,
+                protected: Some(vec!["prod-bucket".to_string()]),
+            },
+            ..CopyOptions::default()
+        };
+
+        // 移動元と移動先が同じ場合は、コピー後の削除でオブジェクトが失われるためエラー
+        let result = rt.block_on(move_object_async(
+            &client, "bucket", "a.txt", "bucket", "a.txt", &options,
+        ));
+        assert!(result.unwrap_err().to_string().contains("同じ"));
+
+        // 保護対象のバケットからの移動には確認が必要
+        let result = rt.block_on(move_object_async(
+            &client,
+            "prod-bucket",
+            "a.txt",
+            "other",
+            "a.txt",
+            &options,
+        ));
+        let e = result.unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<S3Error>(),
+            Some(&S3Error::ConfirmationRequired {
+                bucket: "prod-bucket".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_move() {
+        let mock = MockS3::start(|method, _| match method {
+            "HEAD" => MockResponse::new(200, "")
+                .header("Content-Length", "5")
+                .header("ETag", "\"e\""),
+            "DELETE" => MockResponse::new(204, ""),
+            _ => MockResponse::new(
+                200,
+                "<CopyObjectResult><ETag>&quot;e&quot;</ETag></CopyObjectResult>",
+            ),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // 確認としてバケット名を指定すれば、コピー後にコピー元を削除する
+        let options = CopyOptions {
+            guard: DeleteGuard {
+                confirm: Some("prod-bucket".to_string()),
+                protected: Some(vec!["prod-bucket".to_string()]),
+            },
+            ..CopyOptions::default()
+        };
+        let result = rt
+            .block_on(move_object_async(
+                &mock.client,
+                "prod-bucket",
+                "a.txt",
+                "other",
+                "b.txt",
+                &options,
+            ))
+            .unwrap();
+        assert_eq!(result.e_tag.as_deref(), Some("e"));
+        assert_eq!(mock.count("PUT", "/other/b.txt"), 1);
+        assert_eq!(mock.count("DELETE", "/prod-bucket/a.txt"), 1);
+    }
+}
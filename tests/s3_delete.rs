@@ -0,0 +1,164 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{
+    delete_object_async, delete_objects_async, delete_prefix_async, DeleteFailure, DeleteGuard,
+    DeletePrefixOptions, ObjectId, S3Error,
+};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{dummy_client, MockResponse, MockS3};
+    use super::*;
+
+    /// prod-bucket を保護対象にした確認
+    fn guard(confirm: Option<&str>) -> DeleteGuard {
+        DeleteGuard {
+            confirm: confirm.map(str::to_string),
+            protected: Some(vec!["prod-bucket".to_string()]),
+        }
+    }
+
+    /// logs/ 配下に a.txt と b.txt がある一覧
+    const LISTING: &str =
+        "<ListBucketResult><Name>prod-bucket</Name><IsTruncated>false</IsTruncated>\
+         <Contents><Key>logs/a.txt</Key><Size>1</Size></Contents>\
+         <Contents><Key>logs/b.txt</Key><Size>1</Size></Contents>\
+         </ListBucketResult>";
+
+    /// logs/a.txt は削除でき、logs/b.txt は権限が無い DeleteObjects の結果
+    const DELETE_RESULT: &str = "<DeleteResult><Deleted><Key>logs/a.txt</Key></Deleted>\
+         <Error><Key>logs/b.txt</Key><Code>AccessDenied</Code><Message>denied</Message></Error>\
+         </DeleteResult>";
+
+    fn mock_s3() -> MockS3 {
+        MockS3::start(|method, target| match method {
+            "GET" => MockResponse::new(200, LISTING),
+            "POST" if target.contains("delete") => MockResponse::new(200, DELETE_RESULT),
+            _ => MockResponse::new(204, ""),
+        })
+    }
+
+    #[test]
+    fn test_delete_requires_confirmation() {
+        // 確認の検証は S3 に接続する前に行う
+        let client = dummy_client();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let expected = S3Error::ConfirmationRequired {
+            bucket: "prod-bucket".to_string(),
+        };
+
+        let result = rt.block_on(delete_object_async(
+            &client,
+            "prod-bucket",
+            "a.txt",
+            &guard(None),
+        ));
+        assert_eq!(
+            result.unwrap_err().downcast_ref::<S3Error>(),
+            Some(&expected)
+        );
+
+        let ids = vec![ObjectId::from("a.txt")];
+        let result = rt.block_on(delete_objects_async(
+            &client,
+            "prod-bucket",
+            &ids,
+            &guard(Some("other-bucket")),
+        ));
+        assert_eq!(
+            result.unwrap_err().downcast_ref::<S3Error>(),
+            Some(&expected)
+        );
+    }
+
+    #[test]
+    fn test_delete() {
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // 確認としてバケット名を指定すれば削除できる
+        rt.block_on(delete_object_async(
+            &mock.client,
+            "prod-bucket",
+            "a.txt",
+            &guard(Some("prod-bucket")),
+        ))
+        .unwrap();
+        assert_eq!(mock.count("DELETE", "/prod-bucket/a.txt"), 1);
+
+        // 保護対象でなければ確認は不要
+        rt.block_on(delete_object_async(
+            &mock.client,
+            "dev-bucket",
+            "a.txt",
+            &guard(None),
+        ))
+        .unwrap();
+        assert_eq!(mock.count("DELETE", "/dev-bucket/a.txt"), 1);
+
+        // キーごとの失敗は DeleteReport に入れて返す
+        let ids = vec![ObjectId::from("logs/a.txt"), ObjectId::from("logs/b.txt")];
+        let report = rt
+            .block_on(delete_objects_async(
+                &mock.client,
+                "prod-bucket",
+                &ids,
+                &guard(Some("prod-bucket")),
+            ))
+            .unwrap();
+        assert_eq!(report.deleted, vec![ObjectId::from("logs/a.txt")]);
+        assert_eq!(
+            report.errors,
+            vec![DeleteFailure {
+                key: "logs/b.txt".to_string(),
+                version_id: None,
+                code: Some("AccessDenied".to_string()),
+                message: Some("denied".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_delete_prefix() {
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // dry_run は確認なしで削除予定を返すだけにする
+        let options = DeletePrefixOptions {
+            dry_run: true,
+            guard: guard(None),
+            ..DeletePrefixOptions::default()
+        };
+        let report = rt
+            .block_on(delete_prefix_async(
+                &mock.client,
+                "prod-bucket",
+                "logs/",
+                &options,
+            ))
+            .unwrap();
+        assert_eq!(
+            report.deleted,
+            vec![ObjectId::from("logs/a.txt"), ObjectId::from("logs/b.txt")]
+        );
+        assert_eq!(mock.count("POST", ""), 0);
+
+        let options = DeletePrefixOptions {
+            guard: guard(Some("prod-bucket")),
+            ..DeletePrefixOptions::default()
+        };
+        let report = rt
+            .block_on(delete_prefix_async(
+                &mock.client,
+                "prod-bucket",
+                "logs/",
+                &options,
+            ))
+            .unwrap();
+        assert_eq!(report.deleted, vec![ObjectId::from("logs/a.txt")]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(mock.count("POST", "/prod-bucket/?delete"), 1);
+    }
+}
@@ -0,0 +1,153 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{diff_prefixes_async, DiffKind, DiffOptions};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{dummy_client, temp_dir, MockResponse, MockS3};
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn write(root: &Path, rel: &str, data: &[u8]) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn test_diff_local_dirs() {
+        let root = temp_dir("s3_diff");
+        let (left, right) = (root.join("left"), root.join("right"));
+        write(&left, "same.txt", b"hello");
+        write(&right, "same.txt", b"hello");
+        write(&left, "sub/size.txt", b"short");
+        write(&right, "sub/size.txt", b"much longer");
+        write(&left, "content.txt", b"aaaa");
+        write(&right, "content.txt", b"bbbb");
+        write(&left, "left_only.txt", b"l");
+        write(&right, "right_only.txt", b"r");
+        write(&right, "skip.log", b"r");
+
+        // ローカル同士の比較では S3 には接続しない
+        let client = dummy_client();
+        let options = DiffOptions {
+            exclude: vec!["*.log".to_string()],
+            ..DiffOptions::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let diff = rt
+            .block_on(diff_prefixes_async(
+                &client,
+                left.to_str().unwrap(),
+                right.to_str().unwrap(),
+                &options,
+            ))
+            .unwrap();
+
+        let keys: Vec<&str> = diff.entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "content.txt",
+                "left_only.txt",
+                "right_only.txt",
+                "sub/size.txt"
+            ]
+        );
+        assert!(matches!(
+            &diff.entries[0].kind,
+            DiffKind::ContentMismatch { method, .. } if method == "MD5"
+        ));
+        assert_eq!(diff.entries[1].kind, DiffKind::OnlyLeft);
+        assert_eq!(diff.entries[2].kind, DiffKind::OnlyRight);
+        assert_eq!(
+            diff.entries[3].kind,
+            DiffKind::SizeMismatch { left: 5, right: 11 }
+        );
+        assert_eq!(diff.identical, 1);
+        assert!(diff.unverified.is_empty());
+        assert!(!diff.is_empty());
+
+        let text = diff.to_string();
+        assert!(text.contains("< left_only.txt\n"));
+        assert!(text.contains("> right_only.txt\n"));
+        assert!(text.contains("~ sub/size.txt: size 5 != 11"));
+        assert!(text.ends_with("左のみ 1, 右のみ 1, サイズ違い 1, 内容違い 1, 一致 1, 未確認 0\n"));
+
+        // サイズだけで比較すると、内容の違いは一致として数える
+        let options = DiffOptions {
+            compare_content: false,
+            ..options
+        };
+        let diff = rt
+            .block_on(diff_prefixes_async(
+                &client,
+                left.to_str().unwrap(),
+                right.to_str().unwrap(),
+                &options,
+            ))
+            .unwrap();
+        assert_eq!(diff.entries.len(), 3);
+        assert_eq!(diff.identical, 2);
+
+        // 存在しないディレクトリはエラー
+        assert!(rt
+            .block_on(diff_prefixes_async(
+                &client,
+                root.join("missing").to_str().unwrap(),
+                right.to_str().unwrap(),
+                &options,
+            ))
+            .is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_diff_uneven_multipart_unverified() {
+        // a.txt は複合チェックサム、b.txt はマルチパートの ETag しか無い。
+        // どちらも 1 パート目（4 バイト）から推定した境界では再現できない
+        let mock = MockS3::start(|method, target| {
+            match method {
+            "GET" => MockResponse::new(
+                200,
+                "<ListBucketResult><Name>bucket</Name><IsTruncated>false</IsTruncated>\
+                 <Contents><Key>data/a.txt</Key><Size>11</Size><ETag>&quot;0123456789abcdef-3&quot;</ETag>\
+                 <LastModified>2026-01-01T00:00:00.000Z</LastModified></Contents>\
+                 <Contents><Key>data/b.txt</Key><Size>11</Size><ETag>&quot;fedcba9876543210-3&quot;</ETag>\
+                 <LastModified>2026-01-01T00:00:00.000Z</LastModified></Contents>\
+                 </ListBucketResult>",
+            ),
+            _ if target.contains("partNumber=1") => {
+                MockResponse::new(206, "").header("Content-Length", "4")
+            }
+            _ if target.starts_with("/bucket/data/a.txt") => MockResponse::new(200, "")
+                .header("Content-Length", "11")
+                .header("x-amz-checksum-crc32c", "AAAAAA==-3")
+                .header("x-amz-checksum-type", "COMPOSITE"),
+            _ => MockResponse::new(200, "").header("Content-Length", "11"),
+        }
+        });
+        let root = temp_dir("s3_diff_multipart");
+        write(&root, "a.txt", b"hello world");
+        write(&root, "b.txt", b"hello world");
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let diff = rt
+            .block_on(diff_prefixes_async(
+                &mock.client,
+                "s3://bucket/data/",
+                root.to_str().unwrap(),
+                &DiffOptions::default(),
+            ))
+            .unwrap();
+
+        // 内容の違いではなく、未確認として扱う
+        assert!(diff.entries.is_empty());
+        assert_eq!(diff.unverified, vec!["a.txt", "b.txt"]);
+        assert!(diff.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
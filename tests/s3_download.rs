@@ -0,0 +1,68 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{get_object_to_file_async, GetOptions, S3Error};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{temp_dir, MockResponse, MockS3};
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    /// "hello" を返すモック（e_tag は応答の ETag）
+    fn mock_s3(e_tag: &'static str) -> MockS3 {
+        MockS3::start(move |_, _| {
+            MockResponse::new(200, "hello")
+                .header("ETag", &format!("\"{}\"", e_tag))
+                .header("Last-Modified", "Thu, 01 Jan 2026 00:00:00 GMT")
+        })
+    }
+
+    #[test]
+    fn test_download_to_file() {
+        let dir = temp_dir("s3_download");
+        let path = dir.join("a.txt");
+        fs::write(&path, "old").unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // 検証に失敗した場合は既存のファイルを壊さず、一時ファイルも残さない
+        let mock = mock_s3("00000000000000000000000000000000");
+        let result = rt.block_on(get_object_to_file_async(
+            &mock.client,
+            "bucket",
+            "a.txt",
+            &path,
+            &GetOptions::default(),
+        ));
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<S3Error>(),
+            Some(S3Error::ETagMismatch { .. })
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        assert!(!dir.join(".a.txt.part").exists());
+
+        // 成功した場合は一時ファイルをリネームし、更新日時を Last-Modified に合わせる
+        let mock = mock_s3("5d41402abc4b2a76b9719d911017c592");
+        let info = rt
+            .block_on(get_object_to_file_async(
+                &mock.client,
+                "bucket",
+                "a.txt",
+                &path,
+                &GetOptions::default(),
+            ))
+            .unwrap();
+        assert_eq!(info.size, 5);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello");
+        assert!(!dir.join(".a.txt.part").exists());
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        assert_eq!(
+            modified.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_secs(1_767_225_600)
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
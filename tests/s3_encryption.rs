@@ -0,0 +1,145 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{
+    copy_object_async, get_object_bytes_async, put_bytes_async, CopyOptions, Encryption,
+    EncryptionInfo, GetOptions, PutOptions,
+};
+use std::collections::HashMap;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockResponse, MockS3};
+    use super::*;
+
+    #[test]
+    fn test_reject_bad_customer_key() {
+        // 鍵の長さは S3 に接続する前に検証する
+        let mock = MockS3::start(|_, _| unreachable!());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let key = Encryption::CustomerKey(vec![0u8; 16]);
+
+        let options = PutOptions {
+            encryption: Some(key.clone()),
+            ..PutOptions::default()
+        };
+        let result = rt.block_on(put_bytes_async(
+            &mock.client,
+            "bucket",
+            "a.txt",
+            b"hello".to_vec(),
+            &options,
+        ));
+        assert!(result.unwrap_err().to_string().contains("32 バイト"));
+
+        let options = GetOptions {
+            encryption: Some(key.clone()),
+            ..GetOptions::default()
+        };
+        let result = rt.block_on(get_object_bytes_async(
+            &mock.client,
+            "bucket",
+            "a.txt",
+            &options,
+        ));
+        assert!(result.unwrap_err().to_string().contains("32 バイト"));
+        assert_eq!(mock.count("PUT", ""), 0);
+        assert_eq!(mock.count("GET", ""), 0);
+
+        // 鍵の値は Debug にも出さない
+        assert_eq!(format!("{:?}", key), "CustomerKey(<16 bytes>)");
+    }
+
+    #[test]
+    fn test_encryption_headers() {
+        let mock = MockS3::start(|method, _| match method {
+            "HEAD" => MockResponse::new(200, "")
+                .header("Content-Length", "5")
+                .header("ETag", "\"e\"")
+                .header("x-amz-server-side-encryption-customer-algorithm", "AES256"),
+            "PUT" => MockResponse::new(
+                200,
+                "<CopyObjectResult><ETag>&quot;e&quot;</ETag></CopyObjectResult>",
+            )
+            .header("ETag", "\"e\"")
+            .header("x-amz-server-side-encryption", "aws:kms")
+            .header("x-amz-server-side-encryption-aws-kms-key-id", "arn:key"),
+            _ => MockResponse::new(200, "<LocationConstraint/>"),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // SSE-KMS は鍵 ID と暗号化コンテキストを送り、適用された暗号化を返す
+        let options = PutOptions {
+            encryption: Some(Encryption::Kms {
+                key_id: Some("arn:key".to_string()),
+                context: HashMap::from([("team".to_string(), "data".to_string())]),
+            }),
+            ..PutOptions::default()
+        };
+        let result = rt
+            .block_on(put_bytes_async(
+                &mock.client,
+                "bucket",
+                "kms.txt",
+                b"hello".to_vec(),
+                &options,
+            ))
+            .unwrap();
+        assert_eq!(
+            result.encryption,
+            Some(EncryptionInfo {
+                algorithm: Some("aws:kms".to_string()),
+                kms_key_id: Some("arn:key".to_string()),
+                customer_algorithm: None,
+            })
+        );
+        let header = |key, name| mock.header_values("PUT", key, name);
+        assert_eq!(
+            header(
+                "/bucket/kms.txt",
+                "x-amz-server-side-encryption-aws-kms-key-id"
+            ),
+            ["arn:key"]
+        );
+        assert_eq!(
+            header("/bucket/kms.txt", "x-amz-server-side-encryption-context").len(),
+            1
+        );
+
+        // SSE-C のコピー元は、コピー元用のヘッダーで鍵を送る
+        let options = CopyOptions {
+            source_encryption: Some(Encryption::CustomerKey(vec![1u8; 32])),
+            ..CopyOptions::default()
+        };
+        rt.block_on(copy_object_async(
+            &mock.client,
+            "bucket",
+            "sse-c.txt",
+            "bucket",
+            "copied.txt",
+            &options,
+        ))
+        .unwrap();
+        assert_eq!(
+            header(
+                "/bucket/copied.txt",
+                "x-amz-copy-source-server-side-encryption-customer-algorithm"
+            ),
+            ["AES256"]
+        );
+        assert_eq!(
+            header(
+                "/bucket/copied.txt",
+                "x-amz-copy-source-server-side-encryption-customer-key-md5"
+            )
+            .len(),
+            1
+        );
+        assert!(header(
+            "/bucket/copied.txt",
+            "x-amz-server-side-encryption-customer-algorithm"
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use chrono::{Duration, TimeZone, Utc};
+use rust_std_wrapper::aws::s3::{InventoryOptions, InventoryReport, InventoryStats, ObjectInfo};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(key: &str, size: u64, days_ago: Option<i64>, class: Option<&str>) -> ObjectInfo {
+        let now = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        ObjectInfo {
+            key: key.to_string(),
+            size,
+            e_tag: None,
+            last_modified: days_ago.map(|d| now - Duration::days(d)),
+            storage_class: class.map(str::to_string),
+        }
+    }
+
+    fn stats(objects: u64, bytes: u64) -> InventoryStats {
+        InventoryStats { objects, bytes }
+    }
+
+    #[test]
+    fn test_build() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        let objects = vec![
+            object("data/logs/a.json", 100, Some(5), None),
+            object("data/logs/b.JSON", 300, Some(45), Some("STANDARD_IA")),
+            object("data/img/c.png", 1000, Some(400), Some("GLACIER")),
+            object("data/README", 10, None, Some("STANDARD")),
+        ];
+        let options = InventoryOptions {
+            top_n: 2,
+            ..InventoryOptions::default()
+        };
+        let report = InventoryReport::build("s3://bucket/data/", "data/", objects, now, &options);
+
+        assert_eq!(report.total, stats(4, 1410));
+        assert_eq!(report.by_storage_class["STANDARD"], stats(2, 110));
+        assert_eq!(report.by_storage_class["GLACIER"], stats(1, 1000));
+        assert_eq!(report.by_prefix["logs/"], stats(2, 400));
+        assert_eq!(report.by_prefix["img/"], stats(1, 1000));
+        assert_eq!(report.by_prefix["(root)"], stats(1, 10));
+        assert_eq!(report.by_extension["json"], stats(2, 400));
+        assert_eq!(report.by_extension["(none)"], stats(1, 10));
+
+        let ages: Vec<(&str, u64)> = report
+            .by_age
+            .iter()
+            .map(|(label, s)| (label.as_str(), s.objects))
+            .collect();
+        assert_eq!(
+            ages,
+            vec![
+                ("0-30d", 1),
+                ("30-90d", 1),
+                ("90-365d", 0),
+                ("365d+", 1),
+                ("unknown", 1)
+            ]
+        );
+
+        let largest: Vec<&str> = report.largest.iter().map(|o| o.key.as_str()).collect();
+        assert_eq!(largest, vec!["data/img/c.png", "data/logs/b.JSON"]);
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "category,name,objects,bytes");
+        assert_eq!(lines[1], "total,s3://bucket/data/,4,1410");
+        assert!(lines.contains(&"prefix,logs/,2,400"));
+        assert!(lines.contains(&"largest,data/img/c.png,1,1000"));
+    }
+
+    #[test]
+    fn test_largest_top_n() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        // 同じサイズのオブジェクトが多数ある場合も、サイズ順・同じサイズなら一覧の順で上位 N 件を返す
+        let objects = (0..1000u64).map(|i| object(&format!("k{:04}", i), i % 97, Some(1), None));
+        let options = InventoryOptions {
+            top_n: 5,
+            ..InventoryOptions::default()
+        };
+        let report = InventoryReport::build("s3://bucket/", "", objects, now, &options);
+
+        assert_eq!(report.total.objects, 1000);
+        let largest: Vec<(&str, u64)> = report
+            .largest
+            .iter()
+            .map(|o| (o.key.as_str(), o.size))
+            .collect();
+        assert_eq!(
+            largest,
+            vec![
+                ("k0096", 96),
+                ("k0193", 96),
+                ("k0290", 96),
+                ("k0387", 96),
+                ("k0484", 96)
+            ]
+        );
+
+        let options = InventoryOptions {
+            top_n: 0,
+            ..InventoryOptions::default()
+        };
+        let objects = vec![object("a", 1, None, None)];
+        let report = InventoryReport::build("s3://bucket/", "", objects, now, &options);
+        assert!(report.largest.is_empty());
+    }
+}
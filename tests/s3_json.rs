@@ -0,0 +1,102 @@
+#![cfg(all(
+    feature = "aws",
+    feature = "use_rpassword",
+    feature = "use_dotenv",
+    feature = "use_serde"
+))]
+
+use rust_std_wrapper::aws::s3::{
+    get_json_async, put_json_async, read_jsonl_async, write_jsonl_async,
+};
+use serde::{Deserialize, Serialize};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockResponse, MockS3};
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: u32,
+        name: String,
+    }
+
+    /// PUT で受け取ったボディと Content-Encoding を、同じキーの GET でそのまま返すモック
+    fn mock_s3() -> MockS3 {
+        let stored: Mutex<HashMap<String, (Vec<u8>, Option<String>)>> = Mutex::new(HashMap::new());
+        MockS3::start_with_request(move |request| {
+            let mut stored = stored.lock().unwrap();
+            let key = request.target.split('?').next().unwrap().to_string();
+            match request.method {
+                "PUT" => {
+                    let encoding = request.header("content-encoding").map(str::to_string);
+                    stored.insert(key, (request.body.to_vec(), encoding));
+                    MockResponse::new(200, "").header("ETag", "\"e\"")
+                }
+                _ => {
+                    let (body, encoding) = stored.get(&key).unwrap();
+                    let response = MockResponse::bytes(200, body.clone());
+                    match encoding {
+                        Some(encoding) => response.header("Content-Encoding", encoding),
+                        None => response,
+                    }
+                }
+            }
+        })
+    }
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                id: 1,
+                name: "a".to_string(),
+            },
+            Record {
+                id: 2,
+                name: "b".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        for uri in ["s3://bucket/data.json", "s3://bucket/data.json.gz"] {
+            rt.block_on(put_json_async(&mock.client, uri, &records()))
+                .unwrap();
+            let got: Vec<Record> = rt.block_on(get_json_async(&mock.client, uri)).unwrap();
+            assert_eq!(got, records());
+        }
+
+        // .gz で終わるキーは圧縮して送る
+        let encoding = |key| mock.header_values("PUT", key, "content-encoding");
+        assert!(encoding("/bucket/data.json?").is_empty());
+        assert_eq!(encoding("/bucket/data.json.gz"), ["gzip"]);
+        let content_type = mock.header_values("PUT", "/bucket/data.json", "content-type");
+        assert!(content_type.iter().all(|v| v == "application/json"));
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let uri = "s3://bucket/records.jsonl.gz";
+
+        rt.block_on(write_jsonl_async(&mock.client, uri, records()))
+            .unwrap();
+        let mut stream = rt
+            .block_on(read_jsonl_async::<Record>(&mock.client, uri))
+            .unwrap();
+        let mut got = Vec::new();
+        while let Some(record) = rt.block_on(stream.next()) {
+            got.push(record.unwrap());
+        }
+        assert_eq!(got, records());
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{list_stream, list_stream_matching, KeyPattern, ListEntry};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockResponse, MockS3};
+    use super::*;
+
+    /// 1 ページ目（続きあり）
+    const PAGE1: &str = "<ListBucketResult><Name>bucket</Name><IsTruncated>true</IsTruncated>\
+         <NextContinuationToken>next</NextContinuationToken>\
+         <Contents><Key>logs/a.json</Key><Size>1</Size><ETag>&quot;e1&quot;</ETag></Contents>\
+         <Contents><Key>logs/b.txt</Key><Size>2</Size></Contents>\
+         </ListBucketResult>";
+
+    /// 2 ページ目（最後）
+    const PAGE2: &str = "<ListBucketResult><Name>bucket</Name><IsTruncated>false</IsTruncated>\
+         <Contents><Key>logs/c.json</Key><Size>3</Size></Contents>\
+         <CommonPrefixes><Prefix>logs/2026/</Prefix></CommonPrefixes>\
+         </ListBucketResult>";
+
+    fn mock_s3() -> MockS3 {
+        MockS3::start(|_, target| {
+            if target.contains("continuation-token=next") {
+                MockResponse::new(200, PAGE2)
+            } else {
+                MockResponse::new(200, PAGE1)
+            }
+        })
+    }
+
+    #[test]
+    fn test_list_pagination() {
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut stream = list_stream(&mock.client, "bucket", "logs/", Some("/"));
+
+        // 取得済みのページを使い切るまで、次のページは要求しない
+        let first = rt.block_on(stream.next()).unwrap().unwrap();
+        let ListEntry::Object(first) = first else {
+            panic!("オブジェクトではありません: {:?}", first);
+        };
+        assert_eq!(first.key, "logs/a.json");
+        assert_eq!(first.e_tag.as_deref(), Some("e1"));
+        assert_eq!(mock.count("GET", "list-type=2"), 1);
+
+        let mut rest = Vec::new();
+        while let Some(entry) = rt.block_on(stream.next()) {
+            rest.push(match entry.unwrap() {
+                ListEntry::Object(object) => object.key,
+                ListEntry::Prefix(prefix) => prefix,
+            });
+        }
+        assert_eq!(rest, ["logs/b.txt", "logs/2026/", "logs/c.json"]);
+        assert_eq!(mock.count("GET", "list-type=2"), 2);
+        assert_eq!(mock.count("GET", "delimiter=%2F"), 2);
+        assert!(rt.block_on(stream.next()).is_none());
+    }
+
+    #[test]
+    fn test_list_matching() {
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let pattern = KeyPattern::glob("logs/*.json").unwrap();
+        let mut stream = list_stream_matching(&mock.client, "bucket", &pattern);
+
+        // 固定部分（logs/）で一覧し、残りは取得したキーに対して照合する
+        let mut keys = Vec::new();
+        while let Some(object) = rt.block_on(stream.next_object()) {
+            keys.push(object.unwrap().key);
+        }
+        assert_eq!(keys, ["logs/a.json", "logs/c.json"]);
+        assert_eq!(mock.count("GET", "prefix=logs%2F"), 2);
+    }
+}
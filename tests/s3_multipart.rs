@@ -0,0 +1,120 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{upload_file_multipart_async, MultipartOptions, MIN_PART_SIZE};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{temp_dir, MockResponse, MockS3};
+    use super::*;
+    use aws_sdk_s3::Client;
+    use std::fs;
+    use std::path::Path;
+
+    /// マルチパートアップロードの API だけに応答する S3 のモック
+    /// ※ list_parts_status は ListParts への応答（200 か、404 NoSuchUpload）
+    fn mock_s3(list_parts_status: u16) -> MockS3 {
+        MockS3::start(move |method, target| {
+            let response = match method {
+                "POST" if target.contains("uploads") => MockResponse::new(
+                    200,
+                    "<InitiateMultipartUploadResult><UploadId>new-id</UploadId></InitiateMultipartUploadResult>",
+                ),
+                "POST" => MockResponse::new(
+                    200,
+                    "<CompleteMultipartUploadResult><ETag>\"done\"</ETag></CompleteMultipartUploadResult>",
+                ),
+                "GET" if list_parts_status == 404 => MockResponse::new(
+                    404,
+                    "<Error><Code>NoSuchUpload</Code><Message>gone</Message></Error>",
+                ),
+                "GET" => MockResponse::new(
+                    200,
+                    "<ListPartsResult><IsTruncated>false</IsTruncated></ListPartsResult>",
+                ),
+                "DELETE" => MockResponse::new(204, ""),
+                _ => MockResponse::new(200, ""),
+            };
+            response.header("ETag", "\"part\"")
+        })
+    }
+    /// 前回のアップロードの状態ファイルを書く（part 1 は送信済み）
+    fn write_state(state_file: &Path, path: &Path, file_size: u64) {
+        let modified = fs::metadata(path).unwrap().modified().unwrap();
+        let state = serde_json::json!({
+            "bucket": "bucket",
+            "key": "big.bin",
+            "upload_id": "old-id",
+            "file_size": file_size,
+            "file_modified": modified,
+            "part_size": MIN_PART_SIZE,
+            "parts": [{"part_number": 1, "e_tag": "\"part\""}],
+        });
+        fs::write(state_file, state.to_string()).unwrap();
+    }
+
+    fn upload(client: &Client, path: &Path, state_file: &Path) {
+        let options = MultipartOptions {
+            part_size: MIN_PART_SIZE,
+            state_file: Some(state_file.to_path_buf()),
+            ..MultipartOptions::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(upload_file_multipart_async(
+            client, "bucket", "big.bin", path, &options,
+        ))
+        .unwrap();
+        // 完了したら状態ファイル（と書き込み用の一時ファイル）は残らない
+        assert!(!state_file.exists());
+        assert!(!state_file.with_file_name(".state.json.part").exists());
+    }
+
+    #[test]
+    fn test_resume_state() {
+        let dir = temp_dir("s3_multipart");
+        let path = dir.join("big.bin");
+        fs::write(&path, vec![1u8; 100]).unwrap();
+        let state_file = dir.join("state.json");
+
+        // 状態が一致し、アップロードが残っていれば、送信済みのパートは送らない
+        let mock = mock_s3(200);
+        write_state(&state_file, &path, 100);
+        upload(&mock.client, &path, &state_file);
+        assert_eq!(mock.count("GET", "uploadId=old-id"), 1);
+        assert_eq!(mock.count("POST", "uploads"), 0);
+        assert_eq!(mock.count("PUT", "partNumber"), 0);
+        assert_eq!(mock.count("POST", "uploadId=old-id"), 1);
+
+        // アップロードが S3 側で失われていれば（NoSuchUpload）、最初からやり直す
+        let mock = mock_s3(404);
+        write_state(&state_file, &path, 100);
+        upload(&mock.client, &path, &state_file);
+        assert_eq!(mock.count("DELETE", ""), 0);
+        assert_eq!(mock.count("POST", "uploads"), 1);
+        assert_eq!(mock.count("PUT", "uploadId=new-id"), 1);
+        assert_eq!(mock.count("POST", "uploadId=new-id"), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stale_state_aborts_old_upload() {
+        let dir = temp_dir("s3_multipart_stale");
+        let path = dir.join("big.bin");
+        fs::write(&path, vec![1u8; 100]).unwrap();
+        let state_file = dir.join("state.json");
+
+        // ファイルのサイズが変わっていれば、前回のアップロードを中止して最初からやり直す
+        let mock = mock_s3(200);
+        write_state(&state_file, &path, 50);
+        upload(&mock.client, &path, &state_file);
+        assert_eq!(mock.count("DELETE", "uploadId=old-id"), 1);
+        assert_eq!(mock.count("GET", ""), 0);
+        assert_eq!(mock.count("POST", "uploads"), 1);
+        assert_eq!(mock.count("PUT", "uploadId=new-id"), 1);
+        assert_eq!(mock.count("POST", "uploadId=new-id"), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
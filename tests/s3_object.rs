@@ -0,0 +1,103 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{
+    delete_object_tags_async, exists_async, get_object_tags_async, head_object_async,
+    put_object_tags_async, RestoreStatus,
+};
+use std::collections::HashMap;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockResponse, MockS3};
+    use super::*;
+    use std::sync::Mutex;
+
+    /// タグが 1 つも無い GetObjectTagging の応答
+    const EMPTY_TAGGING: &str = "<Tagging><TagSet></TagSet></Tagging>";
+
+    #[test]
+    fn test_head_object() {
+        let mock = MockS3::start(|_, target| match target {
+            "/bucket/a.txt" => MockResponse::new(200, "")
+                .header("Content-Length", "5")
+                .header("Content-Type", "text/plain")
+                .header("ETag", "\"e\"")
+                .header("x-amz-meta-owner", "deploy")
+                .header(
+                    "x-amz-restore",
+                    "ongoing-request=\"false\", expiry-date=\"Wed, 23 Dec 2026 00:00:00 GMT\"",
+                ),
+            "/bucket/denied.txt" => MockResponse::new(403, ""),
+            _ => MockResponse::new(404, ""),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let metadata = rt
+            .block_on(head_object_async(&mock.client, "bucket", "a.txt"))
+            .unwrap();
+        assert_eq!(metadata.size, 5);
+        assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(metadata.e_tag.as_deref(), Some("e"));
+        assert_eq!(metadata.metadata["owner"], "deploy");
+        // S3 が省略する STANDARD も補う
+        assert_eq!(metadata.storage_class, "STANDARD");
+        assert!(matches!(
+            metadata.restore,
+            Some(RestoreStatus::Restored { expiry: Some(_) })
+        ));
+
+        // 404 は false、それ以外のエラーはエラーとして返す
+        let exists = |key| rt.block_on(exists_async(&mock.client, "bucket", key));
+        assert!(exists("a.txt").unwrap());
+        assert!(!exists("missing.txt").unwrap());
+        assert!(exists("denied.txt").is_err());
+    }
+
+    #[test]
+    fn test_tagging_round_trip() {
+        // PutObjectTagging で受け取った Tagging をそのまま GetObjectTagging で返す
+        let stored = Mutex::new(EMPTY_TAGGING.to_string());
+        let mock = MockS3::start_with_request(move |request| {
+            assert!(request.target.contains("tagging"));
+            let mut stored = stored.lock().unwrap();
+            match request.method {
+                "PUT" => {
+                    *stored = String::from_utf8(request.body.to_vec()).unwrap();
+                    MockResponse::new(200, "")
+                }
+                "DELETE" => {
+                    *stored = EMPTY_TAGGING.to_string();
+                    MockResponse::new(204, "")
+                }
+                _ => MockResponse::new(200, &stored),
+            }
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let tags = HashMap::from([
+            ("team".to_string(), "data".to_string()),
+            ("tier".to_string(), "cold".to_string()),
+        ]);
+
+        rt.block_on(put_object_tags_async(
+            &mock.client,
+            "bucket",
+            "a.txt",
+            &tags,
+        ))
+        .unwrap();
+        let got = rt
+            .block_on(get_object_tags_async(&mock.client, "bucket", "a.txt"))
+            .unwrap();
+        assert_eq!(got, tags);
+
+        rt.block_on(delete_object_tags_async(&mock.client, "bucket", "a.txt"))
+            .unwrap();
+        let got = rt
+            .block_on(get_object_tags_async(&mock.client, "bucket", "a.txt"))
+            .unwrap();
+        assert!(got.is_empty());
+        assert_eq!(mock.count("PUT", "/bucket/a.txt?tagging"), 1);
+    }
+}
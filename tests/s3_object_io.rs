@@ -0,0 +1,162 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{
+    GetOptions, PutOptions, S3ObjectReader, S3ObjectWriter, MIN_PART_SIZE,
+};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockResponse, MockS3};
+    use super::*;
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::time::Duration;
+
+    const DATA: &str = "hello world";
+
+    /// Range 指定の GET に、DATA の該当部分を返すモック
+    fn mock_reader() -> MockS3 {
+        MockS3::start_with_request(|request| {
+            if request.method == "HEAD" {
+                return MockResponse::new(200, "")
+                    .header("Content-Length", "11")
+                    .header("ETag", "\"e\"");
+            }
+            let range = request.header("range").unwrap();
+            let (start, end) = range["bytes=".len()..].split_once('-').unwrap();
+            let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+            MockResponse::new(206, &DATA[start..=end])
+                .header("Content-Range", &format!("bytes {}-{}/11", start, end))
+        })
+    }
+
+    fn mock_writer() -> MockS3 {
+        MockS3::start(|method, target| {
+            let response = match method {
+                "POST" if target.contains("uploads") => MockResponse::new(
+                    200,
+                    "<InitiateMultipartUploadResult><UploadId>new-id</UploadId></InitiateMultipartUploadResult>",
+                ),
+                "POST" => MockResponse::new(
+                    200,
+                    "<CompleteMultipartUploadResult><ETag>\"done\"</ETag></CompleteMultipartUploadResult>",
+                ),
+                "DELETE" => MockResponse::new(204, ""),
+                _ => MockResponse::new(200, ""),
+            };
+            response.header("ETag", "\"part\"")
+        })
+    }
+
+    #[test]
+    fn test_reader_seek_read() {
+        let mock = mock_reader();
+        let mut reader = S3ObjectReader::open_with_client(
+            mock.client.clone(),
+            "bucket",
+            "a.txt",
+            &GetOptions::default(),
+        )
+        .unwrap()
+        .with_read_ahead(4);
+        assert_eq!(reader.size(), 11);
+
+        // 先読みした範囲内は、GET せずに読み込む
+        let mut buf = [0u8; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"he");
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ll");
+        assert_eq!(mock.count("GET", "/bucket/a.txt"), 1);
+
+        // シークした位置から読み込む
+        assert_eq!(reader.seek(SeekFrom::End(-5)).unwrap(), 6);
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "world");
+        assert_eq!(reader.seek(SeekFrom::Current(-11)).unwrap(), 0);
+        assert!(reader.seek(SeekFrom::Current(-1)).is_err());
+
+        // 開いた時点の ETag を If-Match に指定する
+        let if_match = mock.header_values("GET", "/bucket/a.txt", "if-match");
+        assert_eq!(if_match.len(), mock.count("GET", "/bucket/a.txt"));
+        assert!(if_match.iter().all(|v| v == "\"e\""));
+    }
+
+    #[test]
+    fn test_writer_part_flushing() {
+        let part_size = MIN_PART_SIZE as usize;
+
+        // パートサイズに満たなければ PutObject 1 回で送る
+        let mock = mock_writer();
+        let mut writer = S3ObjectWriter::create_with_client(
+            mock.client.clone(),
+            "bucket",
+            "small.bin",
+            &PutOptions::default(),
+        )
+        .unwrap();
+        writer.write_all(b"hello").unwrap();
+        writer.finish().unwrap();
+        assert_eq!(mock.count("PUT", "/bucket/small.bin"), 1);
+        assert_eq!(mock.count("POST", "uploads"), 0);
+
+        // パートサイズ分たまるごとにパートを送り、残りは finish() で送る
+        let mock = mock_writer();
+        let mut writer = S3ObjectWriter::create_with_client(
+            mock.client.clone(),
+            "bucket",
+            "large.bin",
+            &PutOptions::default(),
+        )
+        .unwrap()
+        .with_part_size(part_size);
+        writer.write_all(&vec![b'a'; part_size - 1]).unwrap();
+        assert_eq!(mock.count("POST", "uploads"), 0);
+        writer.write_all(&vec![b'a'; part_size + 2]).unwrap();
+        assert_eq!(mock.count("PUT", "partNumber="), 2);
+        writer.finish().unwrap();
+        assert_eq!(mock.count("PUT", "partNumber="), 3);
+        assert_eq!(mock.count("PUT", "partNumber=3"), 1);
+        assert_eq!(mock.count("POST", "uploadId=new-id"), 1);
+        assert_eq!(mock.count("DELETE", ""), 0);
+    }
+
+    #[test]
+    fn test_writer_drop_aborts() {
+        let part_size = MIN_PART_SIZE as usize;
+        let create = |mock: &MockS3| {
+            let mut writer = S3ObjectWriter::create_with_client(
+                mock.client.clone(),
+                "bucket",
+                "large.bin",
+                &PutOptions::default(),
+            )
+            .unwrap()
+            .with_part_size(part_size);
+            writer.write_all(&vec![b'a'; part_size]).unwrap();
+            writer
+        };
+
+        // finish() を呼ばずに破棄するとアップロードを中止する
+        let mock = mock_writer();
+        drop(create(&mock));
+        assert_eq!(mock.count("DELETE", "uploadId=new-id"), 1);
+
+        // 非同期のコンテキストで破棄しても panic せず、呼び出し元のランタイムで中止する
+        let mock = mock_writer();
+        let writer = create(&mock);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            drop(writer);
+            for _ in 0..100 {
+                if mock.count("DELETE", "uploadId=new-id") > 0 {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+        assert_eq!(mock.count("DELETE", "uploadId=new-id"), 1);
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::KeyPattern;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        let (bucket, pattern) = KeyPattern::from_uri("s3://bucket/logs/2026-*/**/*.json").unwrap();
+        assert_eq!(bucket, "bucket");
+        assert_eq!(pattern.prefix(), "logs/2026-");
+        assert_eq!(pattern.base(), "logs/");
+        assert!(pattern.is_match("logs/2026-01/a.json"));
+        assert!(pattern.is_match("logs/2026-01/x/y/b.json"));
+        assert!(!pattern.is_match("logs/2026-01/a.txt"));
+        assert!(!pattern.is_match("logs/2025-12/a.json"));
+        // * は / を越えない
+        assert!(!pattern.is_match("logs/2026-01/02/a.json.gz"));
+
+        let pattern = KeyPattern::glob("data/file\\*[0-9].csv").unwrap();
+        assert_eq!(pattern.prefix(), "data/file*");
+        assert!(pattern.is_match("data/file*1.csv"));
+        assert!(!pattern.is_match("data/fileX1.csv"));
+
+        let pattern = KeyPattern::glob("*.txt").unwrap();
+        assert_eq!(pattern.prefix(), "");
+        assert_eq!(pattern.base(), "");
+        assert!(pattern.is_match("a.txt"));
+        assert!(!pattern.is_match("dir/a.txt"));
+
+        assert!(KeyPattern::glob("logs/[").is_err());
+    }
+
+    #[test]
+    fn test_regex() {
+        let pattern = KeyPattern::regex(r"^logs/2026-\d{2}/.*\.json$").unwrap();
+        assert_eq!(pattern.prefix(), "logs/2026-");
+        assert!(pattern.is_match("logs/2026-03/x/a.json"));
+        assert!(!pattern.is_match("logs/2026-3/a.json"));
+
+        // 直後に省略可能な量指定子が付く文字は含めない
+        assert_eq!(KeyPattern::regex("^logs?/a").unwrap().prefix(), "log");
+        assert_eq!(KeyPattern::regex("^logs+/a").unwrap().prefix(), "logs");
+        assert_eq!(KeyPattern::regex(r"^a\.b(c|d)").unwrap().prefix(), "a.b");
+        // 最上位の | や、^ で始まらない場合はバケット全体を一覧する
+        assert_eq!(KeyPattern::regex("^a/x|^b/y").unwrap().prefix(), "");
+        assert_eq!(KeyPattern::regex(r"\.json$").unwrap().prefix(), "");
+        assert!(KeyPattern::regex(r"\.json$")
+            .unwrap()
+            .is_match("any/where/a.json"));
+
+        assert!(KeyPattern::regex("^logs/(").is_err());
+    }
+}
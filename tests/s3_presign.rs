@@ -0,0 +1,70 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{presign_get_async, presign_put_async, PresignOptions};
+use std::time::Duration;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::MockS3;
+    use super::*;
+
+    #[test]
+    fn test_presign() {
+        // 署名はローカルで行うため、S3 にはリクエストしない
+        let mock = MockS3::start(|_, _| unreachable!());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let options = PresignOptions {
+            expires_in: Duration::from_secs(600),
+            content_type: Some("text/plain".to_string()),
+            ..PresignOptions::default()
+        };
+
+        let get = rt
+            .block_on(presign_get_async(
+                &mock.client,
+                "bucket",
+                "a b.txt",
+                &options,
+            ))
+            .unwrap();
+        assert!(get.url.contains("/bucket/a%20b.txt?"));
+        assert!(get.url.contains("X-Amz-Expires=600"));
+        assert!(get.url.contains("response-content-type=text%2Fplain"));
+        assert_eq!(get.expires_in, Duration::from_secs(600));
+
+        let put = rt
+            .block_on(presign_put_async(&mock.client, "bucket", "a.txt", &options))
+            .unwrap();
+        assert!(put.url.contains("X-Amz-Expires=600"));
+        assert!(put.url.contains("content-type"));
+        assert_eq!(mock.count("GET", ""), 0);
+        assert_eq!(mock.count("PUT", ""), 0);
+    }
+
+    #[test]
+    fn test_presign_expiry_limit() {
+        // 有効期間は最大 7 日
+        let mock = MockS3::start(|_, _| unreachable!());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let options = PresignOptions {
+            expires_in: Duration::from_secs(7 * 24 * 3600 + 1),
+            ..PresignOptions::default()
+        };
+        assert!(rt
+            .block_on(presign_get_async(&mock.client, "bucket", "a.txt", &options))
+            .is_err());
+        assert!(rt
+            .block_on(presign_put_async(&mock.client, "bucket", "a.txt", &options))
+            .is_err());
+
+        let options = PresignOptions {
+            expires_in: Duration::from_secs(7 * 24 * 3600),
+            ..PresignOptions::default()
+        };
+        assert!(rt
+            .block_on(presign_get_async(&mock.client, "bucket", "a.txt", &options))
+            .is_ok());
+    }
+}
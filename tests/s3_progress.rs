@@ -0,0 +1,141 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{
+    bandwidth_limit, copy_object_async, put_bytes_async, set_bandwidth_limit, CopyOptions,
+    ProgressKind, ProgressReporter, PutOptions, TransferProgress,
+};
+use std::time::Duration;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{MockResponse, MockS3};
+    use super::*;
+
+    #[test]
+    fn test_transfer_progress() {
+        let progress = TransferProgress {
+            bytes_done: 200,
+            bytes_total: 1000,
+            files_done: 1,
+            files_total: 3,
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(progress.rate(), 100.0);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(8)));
+        assert_eq!(progress.fraction(), Some(0.2));
+
+        // 開始直後は速度も残り時間もわからない
+        let progress = TransferProgress {
+            bytes_done: 0,
+            bytes_total: 0,
+            files_done: 0,
+            files_total: 0,
+            elapsed: Duration::ZERO,
+        };
+        assert_eq!(progress.rate(), 0.0);
+        assert_eq!(progress.eta(), None);
+        assert_eq!(progress.fraction(), None);
+    }
+
+    #[test]
+    fn test_bandwidth_limit() {
+        set_bandwidth_limit(Some(1024 * 1024));
+        assert_eq!(bandwidth_limit(), Some(1024 * 1024));
+        // 0 は制限なしとして扱う
+        set_bandwidth_limit(Some(0));
+        assert_eq!(bandwidth_limit(), None);
+        set_bandwidth_limit(None);
+        assert_eq!(bandwidth_limit(), None);
+    }
+
+    #[test]
+    fn test_put_progress_per_chunk() {
+        let mock = MockS3::start(|_, _| MockResponse::new(200, "").header("ETag", "\"e\""));
+        let (reporter, rx) = ProgressReporter::channel();
+        let options = PutOptions {
+            progress: Some(reporter.clone()),
+            ..PutOptions::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(put_bytes_async(
+            &mock.client,
+            "bucket",
+            "a.bin",
+            vec![0u8; 200 * 1024],
+            &options,
+        ))
+        .unwrap();
+
+        // 送信完了時にまとめてではなく、64 KiB ごとに通知する
+        let events: Vec<_> = rx.try_iter().map(|e| e.kind).collect();
+        let chunks: Vec<u64> = events
+            .iter()
+            .filter_map(|kind| match kind {
+                ProgressKind::Transferred { bytes } => Some(*bytes),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(chunks, vec![65536, 65536, 65536, 8192]);
+        assert_eq!(
+            events.first(),
+            Some(&ProgressKind::FileStarted {
+                key: "a.bin".to_string(),
+                size: 200 * 1024
+            })
+        );
+        assert_eq!(
+            events.last(),
+            Some(&ProgressKind::FileFinished {
+                key: "a.bin".to_string()
+            })
+        );
+        assert_eq!(reporter.snapshot().bytes_done, 200 * 1024);
+    }
+
+    #[test]
+    fn test_copy_progress() {
+        let mock = MockS3::start(|method, _| match method {
+            "HEAD" => MockResponse::new(200, "")
+                .header("Content-Length", "5")
+                .header("ETag", "\"e\""),
+            _ => MockResponse::new(
+                200,
+                "<CopyObjectResult><ETag>&quot;e&quot;</ETag></CopyObjectResult>",
+            ),
+        });
+        let (reporter, rx) = ProgressReporter::channel();
+        let options = CopyOptions {
+            progress: Some(reporter),
+            ..CopyOptions::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt
+            .block_on(copy_object_async(
+                &mock.client,
+                "bucket",
+                "a.txt",
+                "bucket",
+                "b.txt",
+                &options,
+            ))
+            .unwrap();
+        assert_eq!(result.e_tag.as_deref(), Some("e"));
+
+        let events: Vec<_> = rx.try_iter().map(|e| e.kind).collect();
+        assert_eq!(
+            events,
+            vec![
+                ProgressKind::FileStarted {
+                    key: "b.txt".to_string(),
+                    size: 5
+                },
+                ProgressKind::Transferred { bytes: 5 },
+                ProgressKind::FileFinished {
+                    key: "b.txt".to_string()
+                },
+            ]
+        );
+    }
+}
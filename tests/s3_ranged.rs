@@ -0,0 +1,76 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{download_file_ranged_async, RangedOptions};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{temp_dir, MockResponse, MockS3};
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// GET の応答を順に返すモック（使い切ったら最後の応答を繰り返す）
+    fn mock_s3(get_statuses: &'static [u16]) -> MockS3 {
+        let gets = AtomicUsize::new(0);
+        MockS3::start(move |method, _| match method {
+            "HEAD" => MockResponse::new(200, "")
+                .header("Content-Length", "11")
+                .header("ETag", "\"opaque\""),
+            _ => {
+                let n = gets.fetch_add(1, Ordering::SeqCst);
+                match get_statuses[n.min(get_statuses.len() - 1)] {
+                    206 => MockResponse::new(206, "hello world"),
+                    412 => MockResponse::new(
+                        412,
+                        "<Error><Code>PreconditionFailed</Code><Message>changed</Message></Error>",
+                    ),
+                    status => MockResponse::new(
+                        status,
+                        "<Error><Code>InternalError</Code><Message>retry</Message></Error>",
+                    ),
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_retry_only_transient_failures() {
+        let dir = temp_dir("s3_ranged");
+        let path = dir.join("a.txt");
+        let options = RangedOptions {
+            max_retries: 3,
+            ..RangedOptions::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // 5xx は再試行する
+        let mock = mock_s3(&[503, 206]);
+        rt.block_on(download_file_ranged_async(
+            &mock.client,
+            "bucket",
+            "a.txt",
+            &path,
+            &options,
+        ))
+        .unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"hello world");
+        assert_eq!(mock.count("GET", "/bucket/a.txt"), 2);
+
+        // 取得中の更新（412）は再試行せずに失敗する
+        let mock = mock_s3(&[412]);
+        let result = rt.block_on(download_file_ranged_async(
+            &mock.client,
+            "bucket",
+            "a.txt",
+            &dir.join("b.txt"),
+            &options,
+        ));
+        assert!(result.is_err());
+        assert_eq!(mock.count("GET", "/bucket/a.txt"), 1);
+        assert!(!dir.join("b.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{LocalStore, MemoryStore, ObjectStore, S3Error, S3Store};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{temp_dir, MockResponse, MockS3};
+    use super::*;
+    use md5::{Digest, Md5};
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    /// オブジェクトをメモリ上に保持する bucket バケットのモック
+    fn mock_bucket() -> MockS3 {
+        let objects: Mutex<BTreeMap<String, Vec<u8>>> = Mutex::new(BTreeMap::new());
+        MockS3::start_with_request(move |request| {
+            let mut objects = objects.lock().unwrap();
+            let (path, query) = request
+                .target
+                .split_once('?')
+                .unwrap_or((request.target, ""));
+            let key = path
+                .strip_prefix("/bucket")
+                .unwrap()
+                .trim_start_matches('/');
+            let e_tag = |data: &[u8]| format!("\"{:x}\"", Md5::digest(data));
+            match request.method {
+                "GET" if query.contains("location") => {
+                    MockResponse::new(200, "<LocationConstraint/>")
+                }
+                "GET" if query.contains("list-type=2") => {
+                    let prefix = query
+                        .split('&')
+                        .find_map(|p| p.strip_prefix("prefix="))
+                        .unwrap_or("")
+                        .replace("%2F", "/");
+                    let contents: String = objects
+                        .iter()
+                        .filter(|(k, _)| k.starts_with(&prefix))
+                        .map(|(k, v)| {
+                            format!(
+                                "<Contents><Key>{}</Key><Size>{}</Size><ETag>{}</ETag></Contents>",
+                                k,
+                                v.len(),
+                                e_tag(v).replace('"', "&quot;")
+                            )
+                        })
+                        .collect();
+                    MockResponse::new(
+                        200,
+                        &format!(
+                            "<ListBucketResult><IsTruncated>false</IsTruncated>{}</ListBucketResult>",
+                            contents
+                        ),
+                    )
+                }
+                "GET" | "HEAD" => match objects.get(key) {
+                    Some(data) => {
+                        MockResponse::bytes(200, data.clone()).header("ETag", &e_tag(data))
+                    }
+                    None => MockResponse::new(
+                        404,
+                        "<Error><Code>NoSuchKey</Code><Message>missing</Message></Error>",
+                    ),
+                },
+                "PUT" => {
+                    let data = match request.header("x-amz-copy-source") {
+                        Some(source) => {
+                            let source = source.strip_prefix("bucket/").unwrap();
+                            objects[source].clone()
+                        }
+                        None => request.body.to_vec(),
+                    };
+                    let e_tag = e_tag(&data);
+                    objects.insert(key.to_string(), data);
+                    MockResponse::new(
+                        200,
+                        &format!(
+                            "<CopyObjectResult><ETag>{}</ETag></CopyObjectResult>",
+                            e_tag.replace('"', "&quot;")
+                        ),
+                    )
+                    .header("ETag", &e_tag)
+                }
+                _ => {
+                    objects.remove(key);
+                    MockResponse::new(204, "")
+                }
+            }
+        })
+    }
+
+    /// どのストアでも同じ結果になることを確認する
+    fn exercise(store: &dyn ObjectStore) {
+        store.put("logs/a.json", b"{\"a\":1}").unwrap();
+        store.put("logs/2026/b.json", b"{}").unwrap();
+        store.put("other.txt", b"other").unwrap();
+
+        assert_eq!(store.get("logs/a.json").unwrap(), b"{\"a\":1}");
+        assert!(store.get("missing.txt").is_err());
+
+        let keys: Vec<String> = store
+            .list("logs/")
+            .unwrap()
+            .into_iter()
+            .map(|o| o.key)
+            .collect();
+        assert_eq!(keys, vec!["logs/2026/b.json", "logs/a.json"]);
+
+        // ETag は内容の MD5
+        let info = store.head("other.txt").unwrap().unwrap();
+        assert_eq!(info.size, 5);
+        assert_eq!(
+            info.e_tag.as_deref(),
+            Some("795f3202b17cb6bc3d4b771d8c6c9eaf")
+        );
+        assert!(store.head("missing.txt").unwrap().is_none());
+
+        store.copy("other.txt", "copied/other.txt").unwrap();
+        assert_eq!(store.get("copied/other.txt").unwrap(), b"other");
+
+        store.delete("other.txt").unwrap();
+        store.delete("other.txt").unwrap();
+        assert!(store.head("other.txt").unwrap().is_none());
+        assert_eq!(store.list("").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        exercise(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_s3_store() {
+        let mock = mock_bucket();
+        let store = S3Store::new_with_client(mock.client.clone(), "s3://bucket/root")
+            .unwrap()
+            .with_protected(&["bucket"]);
+
+        // 保護対象のバケットでは、確認が無いと削除できない
+        store.put("a.txt", b"a").unwrap();
+        let e = store.delete("a.txt").unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<S3Error>(),
+            Some(S3Error::ConfirmationRequired { .. })
+        ));
+        assert_eq!(mock.count("DELETE", ""), 0);
+
+        let store = store.with_confirm("bucket");
+        store.delete("a.txt").unwrap();
+        exercise(&store);
+        // ルートのプレフィックスの下に保存する
+        assert_eq!(mock.count("PUT", "/bucket/root/logs/a.json"), 1);
+    }
+
+    #[test]
+    fn test_local_store() {
+        let root = temp_dir("local_store");
+        let store = LocalStore::new(&root);
+        exercise(&store);
+
+        // ルートの外を指すキーは使えない
+        assert!(store.put("../escape.txt", b"x").is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{sync_down_async, sync_up_async, SyncAction, SyncOptions};
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{temp_dir, MockResponse, MockS3};
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// data/ 配下に b.txt（1 バイト）、c.txt（5 バイト、ローカルより新しい）、d.txt がある一覧
+    const LISTING: &str = "<ListBucketResult><Name>bucket</Name><IsTruncated>false</IsTruncated>\
+         <Contents><Key>data/b.txt</Key><Size>1</Size><LastModified>2000-01-01T00:00:00.000Z</LastModified></Contents>\
+         <Contents><Key>data/c.txt</Key><Size>5</Size><LastModified>2099-01-01T00:00:00.000Z</LastModified></Contents>\
+         <Contents><Key>data/d.txt</Key><Size>5</Size><LastModified>2000-01-01T00:00:00.000Z</LastModified></Contents>\
+         </ListBucketResult>";
+
+    fn mock_s3() -> MockS3 {
+        MockS3::start(|method, target| match method {
+            "GET" if target.contains("list-type=2") => MockResponse::new(200, LISTING),
+            "GET" => MockResponse::new(200, "x").header("ETag", "\"e\""),
+            "DELETE" => MockResponse::new(204, ""),
+            _ => MockResponse::new(200, "").header("ETag", "\"e\""),
+        })
+    }
+
+    fn write(root: &Path, rel: &str, data: &[u8]) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    fn upload(bucket: &str, key: &str, path: &Path) -> SyncAction {
+        SyncAction::Upload {
+            path: path.to_path_buf(),
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sync_up() {
+        let dir = temp_dir("s3_sync_up");
+        write(&dir, "a.txt", b"new");
+        write(&dir, "b.txt", b"changed");
+        write(&dir, "c.txt", b"hello");
+        // 中断したダウンロードの一時ファイルは同期しない
+        write(&dir, ".e.txt.part", b"partial");
+        // ディレクトリへのシンボリックリンクは辿らない（循環しても終わる）
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let expected = vec![
+            upload("bucket", "data/a.txt", &dir.join("a.txt")),
+            upload("bucket", "data/b.txt", &dir.join("b.txt")),
+        ];
+
+        // dry_run は予定を返すだけで、転送しない
+        let options = SyncOptions {
+            dry_run: true,
+            ..SyncOptions::default()
+        };
+        let actions = rt
+            .block_on(sync_up_async(
+                &mock.client,
+                &dir,
+                "s3://bucket/data",
+                &options,
+            ))
+            .unwrap();
+        assert_eq!(actions, expected);
+        assert_eq!(mock.count("PUT", ""), 0);
+
+        // delete を指定すると、ローカルに無いオブジェクトも削除する
+        let options = SyncOptions {
+            delete: true,
+            ..SyncOptions::default()
+        };
+        let actions = rt
+            .block_on(sync_up_async(
+                &mock.client,
+                &dir,
+                "s3://bucket/data/",
+                &options,
+            ))
+            .unwrap();
+        let mut with_delete = expected.clone();
+        with_delete.push(SyncAction::DeleteRemote {
+            bucket: "bucket".to_string(),
+            key: "data/d.txt".to_string(),
+        });
+        assert_eq!(actions, with_delete);
+        assert_eq!(mock.count("PUT", "/bucket/data/a.txt"), 1);
+        assert_eq!(mock.count("PUT", "/bucket/data/b.txt"), 1);
+        assert_eq!(mock.count("PUT", ""), 2);
+        assert_eq!(mock.count("DELETE", "/bucket/data/d.txt"), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_down() {
+        let dir = temp_dir("s3_sync_down");
+        write(&dir, "b.txt", b"b");
+        write(&dir, "c.txt", b"hello");
+        write(&dir, "local_only.txt", b"l");
+
+        let mock = mock_s3();
+        let options = SyncOptions {
+            delete: true,
+            ..SyncOptions::default()
+        };
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let actions = rt
+            .block_on(sync_down_async(
+                &mock.client,
+                "s3://bucket/data/",
+                &dir,
+                &options,
+            ))
+            .unwrap();
+
+        // b.txt はサイズが同じでローカルの方が新しいため、ダウンロードしない
+        let download = |key: &str, rel: &str| SyncAction::Download {
+            bucket: "bucket".to_string(),
+            key: key.to_string(),
+            path: dir.join(rel),
+        };
+        assert_eq!(
+            actions,
+            vec![
+                download("data/c.txt", "c.txt"),
+                download("data/d.txt", "d.txt"),
+                SyncAction::DeleteLocal {
+                    path: dir.join("local_only.txt")
+                },
+            ]
+        );
+        assert_eq!(fs::read(dir.join("d.txt")).unwrap(), b"x");
+        assert!(!dir.join("local_only.txt").exists());
+        assert!(!dir.join(".d.txt.part").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
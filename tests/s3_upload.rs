@@ -0,0 +1,99 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use aws_sdk_s3::types::StorageClass;
+use rust_std_wrapper::aws::s3::{put_bytes_async, put_file_async, PutOptions};
+use std::collections::HashMap;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{temp_dir, MockResponse, MockS3};
+    use super::*;
+    use std::fs;
+
+    fn mock_s3() -> MockS3 {
+        MockS3::start(|_, _| {
+            MockResponse::new(200, "")
+                .header("ETag", "\"e\"")
+                .header("x-amz-version-id", "v1")
+        })
+    }
+
+    #[test]
+    fn test_put_content_type() {
+        let dir = temp_dir("s3_upload");
+        let path = dir.join("index.html");
+        fs::write(&path, "<html></html>").unwrap();
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // ファイル名の拡張子から推測する
+        rt.block_on(put_file_async(
+            &mock.client,
+            "bucket",
+            "site/index",
+            &path,
+            &PutOptions::default(),
+        ))
+        .unwrap();
+        // ファイルが無ければキーの拡張子から推測する
+        rt.block_on(put_bytes_async(
+            &mock.client,
+            "bucket",
+            "data.json",
+            b"{}".to_vec(),
+            &PutOptions::default(),
+        ))
+        .unwrap();
+        // 指定した場合は推測しない
+        let options = PutOptions {
+            content_type: Some("text/plain".to_string()),
+            ..PutOptions::default()
+        };
+        rt.block_on(put_bytes_async(
+            &mock.client,
+            "bucket",
+            "other.json",
+            b"{}".to_vec(),
+            &options,
+        ))
+        .unwrap();
+
+        let content_type = |key| mock.header_values("PUT", key, "content-type");
+        assert_eq!(content_type("/bucket/site/index"), ["text/html"]);
+        assert_eq!(content_type("/bucket/data.json"), ["application/json"]);
+        assert_eq!(content_type("/bucket/other.json"), ["text/plain"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_put_metadata() {
+        let mock = mock_s3();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let options = PutOptions {
+            metadata: HashMap::from([("owner".to_string(), "deploy".to_string())]),
+            cache_control: Some("max-age=60".to_string()),
+            storage_class: Some(StorageClass::StandardIa),
+            ..PutOptions::default()
+        };
+
+        let result = rt
+            .block_on(put_bytes_async(
+                &mock.client,
+                "bucket",
+                "a.txt",
+                b"hello".to_vec(),
+                &options,
+            ))
+            .unwrap();
+        assert_eq!(result.e_tag.as_deref(), Some("e"));
+        assert_eq!(result.version_id.as_deref(), Some("v1"));
+
+        let header = |name| mock.header_values("PUT", "/bucket/a.txt", name);
+        assert_eq!(header("x-amz-meta-owner"), ["deploy"]);
+        assert_eq!(header("cache-control"), ["max-age=60"]);
+        assert_eq!(header("x-amz-storage-class"), ["STANDARD_IA"]);
+    }
+}
@@ -0,0 +1,90 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use rust_std_wrapper::aws::s3::{validate_bucket_name, S3Uri};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let uri: S3Uri = "s3://my-bucket/logs/2026/app.json".parse().unwrap();
+        assert_eq!(uri.bucket(), "my-bucket");
+        assert_eq!(uri.key(), "logs/2026/app.json");
+        assert_eq!(uri.to_string(), "s3://my-bucket/logs/2026/app.json");
+
+        // バケット直下
+        let root: S3Uri = "s3://my-bucket".parse().unwrap();
+        assert_eq!(root.key(), "");
+        assert!(root.is_dir());
+
+        // s3:// で始まらない場合やバケット名が不正な場合はエラー
+        assert!("my-bucket/key".parse::<S3Uri>().is_err());
+        assert!("s3://My_Bucket/key".parse::<S3Uri>().is_err());
+    }
+
+    #[test]
+    fn test_join_parent_file_name() {
+        let dir: S3Uri = "s3://my-bucket/logs".parse().unwrap();
+        let file = dir.join("2026/app.json");
+        assert_eq!(file.to_string(), "s3://my-bucket/logs/2026/app.json");
+        assert_eq!(file.file_name(), Some("app.json"));
+
+        // 親ディレクトリを辿る
+        let parent = file.parent().unwrap();
+        assert_eq!(parent.to_string(), "s3://my-bucket/logs/2026/");
+        assert_eq!(parent.file_name(), None);
+        assert_eq!(parent.parent().unwrap().key(), "logs/");
+        assert_eq!(parent.parent().unwrap().parent().unwrap().key(), "");
+        assert!(dir.to_dir().parent().unwrap().parent().is_none());
+    }
+
+    #[test]
+    fn test_parse_https_url() {
+        // 仮想ホスト形式（バケット名にピリオドを含む場合も）
+        let uri: S3Uri = "https://my.bucket.s3.ap-northeast-1.amazonaws.com/a/b%20c.txt?x=1"
+            .parse()
+            .unwrap();
+        assert_eq!(uri.bucket(), "my.bucket");
+        assert_eq!(uri.key(), "a/b c.txt");
+
+        // パス形式
+        let uri =
+            S3Uri::from_url("https://s3.ap-northeast-1.amazonaws.com/my-bucket/a/b.txt").unwrap();
+        assert_eq!(uri.bucket(), "my-bucket");
+        assert_eq!(uri.key(), "a/b.txt");
+
+        // "s3-" や "s3" で始まるバケット名
+        let uri = S3Uri::from_url("https://s3-logs.s3.us-east-1.amazonaws.com/key").unwrap();
+        assert_eq!(uri.bucket(), "s3-logs");
+        assert_eq!(uri.key(), "key");
+        let uri = S3Uri::from_url("https://s3.data.s3-us-west-2.amazonaws.com/key").unwrap();
+        assert_eq!(uri.bucket(), "s3.data");
+        let uri = S3Uri::from_url("https://s3-us-west-2.amazonaws.com/s3-logs/key").unwrap();
+        assert_eq!(uri.bucket(), "s3-logs");
+
+        // S3 以外のホストはエラー
+        assert!(S3Uri::from_url("https://example.com/my-bucket/a").is_err());
+    }
+
+    #[test]
+    fn test_serde() {
+        let uri: S3Uri = "s3://my-bucket/a/b.txt".parse().unwrap();
+        let json = serde_json::to_string(&uri).unwrap();
+        assert_eq!(json, r#""s3://my-bucket/a/b.txt""#);
+        let uri2: S3Uri = serde_json::from_str(&json).unwrap();
+        assert_eq!(uri, uri2);
+        assert!(serde_json::from_str::<S3Uri>(r#""not-a-uri""#).is_err());
+    }
+
+    #[test]
+    fn test_validate_bucket_name() {
+        assert!(validate_bucket_name("my-bucket.example").is_ok());
+        assert!(validate_bucket_name("ab").is_err());
+        assert!(validate_bucket_name("-bucket").is_err());
+        assert!(validate_bucket_name("my..bucket").is_err());
+        assert!(validate_bucket_name("192.168.0.1").is_err());
+        assert!(validate_bucket_name("xn--bucket").is_err());
+        assert!(validate_bucket_name("bucket-s3alias").is_err());
+    }
+}
@@ -0,0 +1,75 @@
+#![cfg(all(feature = "aws", feature = "use_rpassword", feature = "use_dotenv"))]
+
+use chrono::{DateTime, TimeZone, Utc};
+use rust_std_wrapper::aws::s3::{restore_plan, ObjectVersion};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    fn version(
+        key: &str,
+        id: &str,
+        hour: u32,
+        is_latest: bool,
+        is_delete_marker: bool,
+    ) -> ObjectVersion {
+        ObjectVersion {
+            key: key.to_string(),
+            version_id: id.to_string(),
+            is_latest,
+            is_delete_marker,
+            size: 0,
+            e_tag: None,
+            last_modified: Some(at(hour)),
+        }
+    }
+
+    #[test]
+    fn test_restore_plan() {
+        let versions = vec![
+            // 10 時以降に上書きされた
+            version("a.json", "a2", 12, true, false),
+            version("a.json", "a1", 9, false, false),
+            // 10 時以降に変更されていない
+            version("b.json", "b1", 8, true, false),
+            // 10 時以降に削除された
+            version("c.json", "c-del", 11, true, true),
+            version("c.json", "c1", 7, false, false),
+            // 10 時時点では削除されていた
+            version("d.json", "d2", 11, true, false),
+            version("d.json", "d-del", 9, false, true),
+            version("d.json", "d1", 8, false, false),
+            // 10 時以降に作成された
+            version("e.json", "e1", 11, true, false),
+        ];
+        let plan = restore_plan(&versions, at(10));
+        let restored: Vec<(&str, &str)> = plan
+            .iter()
+            .map(|v| (v.key.as_str(), v.version_id.as_str()))
+            .collect();
+        assert_eq!(restored, vec![("a.json", "a1"), ("c.json", "c1")]);
+
+        // ちょうど同じ時刻のバージョンも対象に含める
+        let plan = restore_plan(&versions, at(9));
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].version_id, "a1");
+    }
+
+    #[test]
+    fn test_restore_plan_same_timestamp() {
+        // 同じ秒に 2 回上書きされた場合は、一覧で先にある（新しい）方を選ぶ
+        let versions = vec![
+            version("a.json", "a3", 12, true, false),
+            version("a.json", "a2", 9, false, false),
+            version("a.json", "a1", 9, false, false),
+        ];
+        let plan = restore_plan(&versions, at(10));
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].version_id, "a2");
+    }
+}